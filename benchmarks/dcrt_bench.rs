@@ -8,50 +8,37 @@ static ARTICLE_PRIMES: &[u32] = &[
 ];
 
 /// Função de benchmark genérica que agora aceita todos os parâmetros do artigo
-fn run_benchmarks_for_n<const N_POLY: usize>(
+fn run_benchmarks(
     c: &mut Criterion,
-    rho: u32,
-    gamma: f64,
-    big_l: usize,     // Parâmetro L da tabela (para produto interno)
-    l_param: usize,   // Parâmetro ℓ da tabela (para produto externo)
-    log_b_param: u32, // Parâmetro log b da tabela (para produto externo)
+    params: &FhezParameters,
+    big_l: usize, // Parâmetro L da tabela (para produto interno)
+    backend: DcrtBackend,
     id: &str,
 ) {
     let mut group = c.benchmark_group("FHEZ Operations (Article Params)");
 
     // --- Setup ---
-    let mut plan = Plan::new(N_POLY, Method::Measure(Duration::from_millis(10)));
-
-    // --- CORREÇÃO APLICADA AQUI ---
-    // A potência é calculada sobre um inteiro (u64) e depois convertida para f64
-    let b_as_f64 = (1u64 << log_b_param) as f64;
-    let context = DcrtContext::new(
-        ARTICLE_PRIMES,
-        gamma,
-        l_param as f64,
-        b_as_f64,
-        N_POLY as f64,
-    );
+    let context = DcrtContext::with_backend(params, backend).unwrap();
 
-    let poly_a = BigPolynomial::rand(N_POLY, rho, N_POLY as u32);
-    let poly_b = BigPolynomial::rand(N_POLY, rho, N_POLY as u32);
+    let poly_a = BigPolynomial::rand(params.n, params.rho as u32, params.n as u32);
+    let poly_b = BigPolynomial::rand(params.n, params.rho as u32, params.n as u32);
 
     // --- Benchmarks de Conversão ---
     group.bench_with_input(BenchmarkId::new("to_dcrt", id), &poly_a, |b, p| {
-        b.iter(|| to_dcrt::<N_POLY>(black_box(p), black_box(&context), black_box(&mut plan)))
+        b.iter(|| to_dcrt(black_box(p), black_box(&context), black_box(params)))
     });
 
-    let dcrt_a = to_dcrt::<N_POLY>(&poly_a, &context, &mut plan);
-    let dcrt_b = to_dcrt::<N_POLY>(&poly_b, &context, &mut plan);
+    let dcrt_a = to_dcrt(&poly_a, &context, params);
+    let dcrt_b = to_dcrt(&poly_b, &context, params);
 
     group.bench_with_input(BenchmarkId::new("from_dcrt", id), &dcrt_a, |b, p| {
         b.iter_batched(
             || p.clone(),
             |mut p_clone| {
-                from_dcrt::<N_POLY>(
+                from_dcrt(
                     black_box(&mut p_clone),
                     black_box(&context),
-                    black_box(&mut plan),
+                    black_box(params),
                 )
             },
             BatchSize::SmallInput,
@@ -81,21 +68,21 @@ fn run_benchmarks_for_n<const N_POLY: usize>(
     );
 
     // --- Benchmark do Produto Externo ---
-    let b_param_u64 = 1u64 << log_b_param;
-    let vector_ct: Vec<Dcrt> = (0..l_param).map(|_| dcrt_a.clone()).collect();
+    let vector_ct: Vec<Dcrt> = (0..params.l).map(|_| dcrt_a.clone()).collect();
     let scalar_ct = dcrt_b.clone();
 
     group.bench_function(BenchmarkId::new("External Product (DCRT)", id), |b| {
         b.iter_batched(
-            || (vector_ct.clone(), scalar_ct.clone(), plan.clone()),
-            |(v_ct, mut s_ct, mut p)| {
-                external_product::<N_POLY>(
+            || (vector_ct.clone(), scalar_ct.clone()),
+            |(v_ct, mut s_ct)| {
+                external_product(
                     black_box(&v_ct),
                     black_box(&mut s_ct),
-                    black_box(&context),
-                    black_box(&mut p),
-                    l_param,
-                    b_param_u64,
+                    black_box(params),
+                    &mut DcrtRing {
+                        context: black_box(&context),
+                        params,
+                    },
                 )
             },
             BatchSize::SmallInput,
@@ -108,14 +95,148 @@ fn run_benchmarks_for_n<const N_POLY: usize>(
 // Funções "trampolim" agora passam todos os parâmetros corretos para cada linha
 fn benchmark_row1(c: &mut Criterion) {
     // Parâmetros da Linha 1: L=114, ℓ=10, log b=24
-    run_benchmarks_for_n::<256>(c, 56, 206.0, 114, 10, 24, "Row 1 (N=256, rho=56)");
+    let params = FhezParameters {
+        n: 256,
+        b: 1 << 24,
+        l: 10,
+        gamma: 206.0,
+        rho: 56,
+        eta: 96,
+        t: 4,
+        primes: ARTICLE_PRIMES,
+    };
+    run_benchmarks(c, &params, 114, DcrtBackend::Fft, "Row 1 (N=256, rho=56)");
+    // Mesmos parâmetros com a NTT exata
+    run_benchmarks(
+        c,
+        &params,
+        114,
+        DcrtBackend::Ntt,
+        "Row 1 NTT (N=256, rho=56)",
+    );
 }
 
 fn benchmark_row3(c: &mut Criterion) {
     // Parâmetros da Linha 3: L=86, ℓ=21, log b=11
-    run_benchmarks_for_n::<128>(c, 69, 204.0, 86, 21, 11, "Row 3 (N=128, rho=69)");
+    let params = FhezParameters {
+        n: 128,
+        b: 1 << 11,
+        l: 21,
+        gamma: 204.0,
+        rho: 69,
+        eta: 96,
+        t: 4,
+        primes: ARTICLE_PRIMES,
+    };
+    run_benchmarks(c, &params, 86, DcrtBackend::Fft, "Row 3 (N=128, rho=69)");
+}
+
+// Compara a FFT de n pontos sobre dados reais com a FFT dobrada de n / 2 pontos usada em to_dcrt
+fn benchmark_folding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Negacyclic FFT (one layer)");
+
+    for n in [256usize, 1024] {
+        // Versão antiga: torção pelas raízes 2n-ésimas e FFT complexa de tamanho n
+        let plan = Plan::new(n, Method::Measure(Duration::from_millis(10)));
+        let mut stack_buffer = GlobalPodBuffer::new(plan.fft_scratch().unwrap());
+        let full: Vec<c64> = (0..n)
+            .map(|j| {
+                let theta = std::f64::consts::PI * j as f64 / n as f64;
+                c64::new(theta.cos(), theta.sin()) * (j as f64)
+            })
+            .collect();
+        group.bench_with_input(BenchmarkId::new("n point", n), &full, |b, layer| {
+            b.iter_batched(
+                || layer.clone(),
+                |mut layer| plan.fwd(black_box(&mut layer), PodStack::new(&mut stack_buffer)),
+                BatchSize::SmallInput,
+            )
+        });
+
+        // Versão dobrada: coeficientes j e j + n / 2 num mesmo complexo
+        let tables = FftTables::new(n);
+        let folded: Vec<c64> = (0..n / 2)
+            .map(|j| c64::new(j as f64, (j + n / 2) as f64) * tables.twiddles[j])
+            .collect();
+        group.bench_with_input(
+            BenchmarkId::new("n / 2 point (folded)", n),
+            &folded,
+            |b, layer| {
+                b.iter_batched(
+                    || layer.clone(),
+                    |mut layer| tables.forward(black_box(&mut layer)),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+// Compara os produtos mod x0 da cifragem e da decifragem em BigPolynomial e FixedPolynomial
+fn benchmark_fixed_products(c: &mut Criterion) {
+    let mut group = c.benchmark_group("GAHE products mod x0");
+
+    for params in [
+        FhezParameters::small_test(),
+        FhezParameters::article_line1(),
+    ] {
+        let ctx = GaheContext::new(&params).unwrap();
+        let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+        let message = BigPolynomial::new(ctx.n);
+        let ciphertext = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &message, &mut ());
+        let id = format!("n={} gamma={}", ctx.n, ctx.gamma);
+
+        group.bench_function(
+            BenchmarkId::new("encrypt_scalar (BigPolynomial)", &id),
+            |b| b.iter(|| encrypt_scalar::<BigPolynomial>(&sk, &ctx, black_box(&message), &mut ())),
+        );
+        group.bench_function(
+            BenchmarkId::new("encrypt_scalar (FixedPolynomial)", &id),
+            |b| {
+                b.iter(|| {
+                    encrypt_scalar::<FixedPolynomial<COEFFICIENT_LIMBS>>(
+                        &sk,
+                        &ctx,
+                        black_box(&message),
+                        &mut (),
+                    )
+                })
+            },
+        );
+        group.bench_function(
+            BenchmarkId::new("decrypt_scalar (BigPolynomial)", &id),
+            |b| {
+                b.iter(|| {
+                    decrypt_scalar::<BigPolynomial>(&sk, &ctx, black_box(&ciphertext), &mut ())
+                })
+            },
+        );
+        group.bench_function(
+            BenchmarkId::new("decrypt_scalar (FixedPolynomial)", &id),
+            |b| {
+                b.iter(|| {
+                    decrypt_scalar::<FixedPolynomial<COEFFICIENT_LIMBS>>(
+                        &sk,
+                        &ctx,
+                        black_box(&ciphertext),
+                        &mut (),
+                    )
+                })
+            },
+        );
+    }
+
+    group.finish();
 }
 
 // Agrupa os benchmarks para execução
-criterion_group!(benches, benchmark_row1, benchmark_row3);
+criterion_group!(
+    benches,
+    benchmark_row1,
+    benchmark_row3,
+    benchmark_folding,
+    benchmark_fixed_products
+);
 criterion_main!(benches);
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use num_integer::Integer;
 const PI: f64 = std::f64::consts::PI;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -43,6 +44,171 @@ impl BigPolynomial {
         }
         res
     }
+
+    /// Multiplies by the monomial X^k in Z[X] / <X^n + 1>, with n the number of coefficients
+    ///
+    /// Shifts the coefficients by k and flips the sign of the ones that wrap around, since
+    /// X^n = - 1. Any k works, negative or not, as X^(2n) = 1
+    pub fn mul_by_monomial(&self, k: i64) -> BigPolynomial {
+        let n = self.degree();
+        let mut res = BigPolynomial::new(n);
+        if n == 0 {
+            return res;
+        }
+
+        let shift = k.rem_euclid(2 * n as i64) as usize;
+        for (j, coef) in self.coefficients.iter().enumerate() {
+            let target = j + shift;
+            if (target / n).is_multiple_of(2) {
+                res.coefficients[target % n] = coef.clone();
+            } else {
+                res.coefficients[target % n] = -coef;
+            }
+        }
+        res
+    }
+}
+
+impl BigPolynomial {
+    /// Inverse of the polynomial in Z_modulus[X] / <X^n + 1>, where n is the number of coefficients
+    ///
+    /// The modulus may be composite: when the Euclidean algorithm meets a zero divisor it
+    /// splits the modulus into coprime factors and recombines with the CRT, and prime powers
+    /// are handled by Hensel lifting. Returns None if the polynomial is not invertible
+    pub fn inverse_mod(&self, modulus: &BigInt) -> Option<BigPolynomial> {
+        if *modulus <= BigInt::one() || self.coefficients.is_empty() {
+            return None;
+        }
+
+        let factor = match self.euclid_inverse(modulus) {
+            Ok(inverse) => return inverse,
+            Err(factor) => factor,
+        };
+
+        // Grows the factor until it is coprime with its cofactor, so modulus = m1 * m2
+        let mut m1 = factor.clone();
+        loop {
+            let common = m1.gcd(&(modulus / &m1));
+            if common.is_one() {
+                break;
+            }
+            m1 *= common;
+        }
+        let m2 = modulus / &m1;
+
+        if m2.is_one() {
+            // Every prime of the modulus divides the factor: lifts the inverse mod factor
+            // with the Newton iteration inv := inv * (2 - a * inv), which squares the modulus
+            let mut inverse = self.inverse_mod(&factor)?;
+            let mut two = BigPolynomial::new(self.degree());
+            two.coefficients[0] = BigInt::from(2u32);
+            loop {
+                let product = (self * &inverse) % modulus;
+                if product.coefficients[0].is_one()
+                    && product.coefficients[1..].iter().all(|c| c.is_zero())
+                {
+                    return Some(inverse);
+                }
+                inverse = (&inverse * &(&two - &product)) % modulus;
+            }
+        }
+
+        // Combines the inverses mod m1 and mod m2 coefficient by coefficient
+        let inv_1 = self.inverse_mod(&m1)?;
+        let inv_2 = self.inverse_mod(&m2)?;
+        let m1_inv = m1.modinv(&m2)?;
+        let coefficients = inv_1
+            .coefficients
+            .iter()
+            .zip(inv_2.coefficients.iter())
+            .map(|(a1, a2)| a1 + &m1 * ((a2 - a1) * &m1_inv).rem_euclid(&m2))
+            .collect();
+        Some(BigPolynomial { coefficients })
+    }
+
+    /// Checks if the polynomial is invertible in Z_modulus[X] / <X^n + 1>
+    pub fn is_invertible_mod(&self, modulus: &BigInt) -> bool {
+        self.inverse_mod(modulus).is_some()
+    }
+
+    /// Extended Euclidean algorithm between the polynomial and X^n + 1 over Z_m
+    ///
+    /// Returns Err with a nontrivial factor of m when a coefficient that must be inverted
+    /// is a zero divisor, otherwise the inverse or None if the gcd is not a unit
+    fn euclid_inverse(&self, m: &BigInt) -> Result<Option<BigPolynomial>, BigInt> {
+        let n = self.degree();
+
+        // Inverts c mod m or reports the factor gcd(c, m)
+        let invert = |c: &BigInt| -> Result<BigInt, BigInt> {
+            let common = c.gcd(m);
+            if common.is_one() {
+                Ok(c.modinv(m).expect("c is coprime with m"))
+            } else {
+                Err(common)
+            }
+        };
+
+        // r0 := X^n + 1 and r1 := a, keeping s_i such that s_i * a = r_i mod X^n + 1
+        let mut r0 = vec![BigInt::zero(); n + 1];
+        r0[0] = BigInt::one();
+        r0[n] = BigInt::one();
+        let mut r1: Vec<BigInt> = self.coefficients.iter().map(|c| c.rem_euclid(m)).collect();
+        trim(&mut r1);
+
+        let mut s0: Vec<BigInt> = Vec::new();
+        let mut s1 = vec![BigInt::one()];
+
+        while r1.len() > 1 {
+            let lc_inv = invert(&r1[r1.len() - 1])?;
+
+            // Long division r0 = quotient * r1 + r0
+            let mut quotient = vec![BigInt::zero(); r0.len() - r1.len() + 1];
+            for i in (0..quotient.len()).rev() {
+                let coef = (&r0[i + r1.len() - 1] * &lc_inv).rem_euclid(m);
+                for (j, c) in r1.iter().enumerate() {
+                    r0[i + j] = (&r0[i + j] - &coef * c).rem_euclid(m);
+                }
+                quotient[i] = coef;
+            }
+            trim(&mut r0);
+
+            // s0 := s0 - quotient * s1
+            let mut s_next = vec![BigInt::zero(); (quotient.len() + s1.len() - 1).max(s0.len())];
+            for (i, c) in s0.iter().enumerate() {
+                s_next[i] += c;
+            }
+            for (i, q) in quotient.iter().enumerate() {
+                for (j, c) in s1.iter().enumerate() {
+                    s_next[i + j] -= q * c;
+                }
+            }
+            for c in s_next.iter_mut() {
+                *c = c.rem_euclid(m);
+            }
+            trim(&mut s_next);
+
+            std::mem::swap(&mut r0, &mut r1);
+            s0 = std::mem::replace(&mut s1, s_next);
+        }
+
+        // The gcd is the constant r1, which must be a unit
+        let gcd = match r1.first() {
+            Some(gcd) => gcd,
+            None => return Ok(None),
+        };
+        let gcd_inv = invert(gcd)?;
+        let mut coefficients: Vec<BigInt> =
+            s1.iter().map(|c| (c * &gcd_inv).rem_euclid(m)).collect();
+        coefficients.resize(n, BigInt::zero());
+        Ok(Some(BigPolynomial { coefficients }))
+    }
+}
+
+/// Removes the leading zero coefficients of a dense coefficient vector
+fn trim(coefficients: &mut Vec<BigInt>) {
+    while coefficients.last().is_some_and(|c| c.is_zero()) {
+        coefficients.pop();
+    }
 }
 
 // Adição
@@ -60,14 +226,14 @@ impl<'a> Add<&'a BigPolynomial> for BigPolynomial {
     }
 }
 
-impl<'a> Add<BigPolynomial> for &'a BigPolynomial {
+impl Add<BigPolynomial> for &BigPolynomial {
     type Output = BigPolynomial;
     fn add(self, rhs: BigPolynomial) -> Self::Output {
         self + &rhs
     }
 }
 
-impl<'a, 'b> Add<&'b BigPolynomial> for &'a BigPolynomial {
+impl<'b> Add<&'b BigPolynomial> for &BigPolynomial {
     type Output = BigPolynomial;
     fn add(self, rhs: &'b BigPolynomial) -> Self::Output {
         let degree = self.degree().max(rhs.degree());
@@ -99,14 +265,14 @@ impl<'a> Sub<&'a BigPolynomial> for BigPolynomial {
     }
 }
 
-impl<'a> Sub<BigPolynomial> for &'a BigPolynomial {
+impl Sub<BigPolynomial> for &BigPolynomial {
     type Output = BigPolynomial;
     fn sub(self, rhs: BigPolynomial) -> Self::Output {
         self - &rhs
     }
 }
 
-impl<'a, 'b> Sub<&'b BigPolynomial> for &'a BigPolynomial {
+impl<'b> Sub<&'b BigPolynomial> for &BigPolynomial {
     type Output = BigPolynomial;
     fn sub(self, rhs: &'b BigPolynomial) -> Self::Output {
         let degree = self.degree().max(rhs.degree());
@@ -138,14 +304,14 @@ impl<'a> Mul<&'a BigPolynomial> for BigPolynomial {
     }
 }
 
-impl<'a> Mul<BigPolynomial> for &'a BigPolynomial {
+impl Mul<BigPolynomial> for &BigPolynomial {
     type Output = BigPolynomial;
     fn mul(self, rhs: BigPolynomial) -> Self::Output {
         self * &rhs
     }
 }
 
-impl<'a, 'b> Mul<&'b BigPolynomial> for &'a BigPolynomial {
+impl<'b> Mul<&'b BigPolynomial> for &BigPolynomial {
     type Output = BigPolynomial;
     fn mul(self, rhs: &'b BigPolynomial) -> Self::Output {
         let n = self.degree().max(rhs.degree());
@@ -0,0 +1,385 @@
+use crate::prelude::*;
+
+/// Public key material used to bootstrap the ciphertexts of a GAHE secret key
+///
+/// The refreshing follows [Per21]: since x0 = p * q0, the phase c / p mod 1 of an integer
+/// ciphertext c equals the sum of s_i * (2^i * c / x0) mod 1, where s_i are the bits of q0.
+/// Each term is public, so scaling it to Z_2n gives an exponent a_i and the blind rotation
+/// computes X^(sum a_i * s_i) with vector ciphertexts of the bits s_i.
+///
+/// This is the coefficient form of the key, see BootstrappingKey for the one bootstrap uses
+#[derive(Debug, Clone)]
+pub struct BootstrapKeyCoefficients {
+    /// Extracts the constant coefficient of c * k_inv as an integer ciphertext
+    pub extraction_key: ExtractionKey,
+
+    /// Scalar ciphertexts of round(p / 2t) * b^i, used to encode public test polynomials
+    pub test_vector_key: Vec<BigPolynomial>,
+
+    /// One vector ciphertext for each bit of q0 = x0 / p, starting from the least significant
+    pub bit_keys: Vec<VectorCiphertext>,
+
+    /// Public modulus of the ciphertexts
+    pub x0: BigInt,
+
+    /// Fingerprint of the key whose ciphertexts can be bootstrapped
+    pub fingerprint: u64,
+}
+
+impl BootstrapKeyCoefficients {
+    /// Generates the bootstrapping key of a secret key, drawing the noise from rng
+    pub fn generate<R: Rng + ?Sized>(
+        sk: &GaheSecretKey,
+        context: &GaheContext,
+        params: &FhezParameters,
+        rng: &mut R,
+    ) -> Self {
+        let n = context.n;
+        let b_big = BigInt::from(params.b);
+        let powers_of_b: Vec<BigInt> = (0..params.l).map(|i| b_big.pow(i as u32)).collect();
+
+        let extraction_key = ExtractionKey::generate(sk, context, params, rng);
+
+        // Encryptions of round(p / 2t) * b^i without the message scaling
+        let delta = round_bigint_division(&sk.p, &(&context.t << 1));
+        let delta_k = (&delta * &sk.k) % &sk.x0;
+        let test_vector_key = powers_of_b
+            .iter()
+            .map(|power| {
+                let zero =
+                    sample_r_with_rng(context.gamma, context.rho, &sk.p, &sk.k, &sk.x0, n, rng);
+                (&zero + &(power * &delta_k)) % &sk.x0
+            })
+            .collect();
+
+        // Vector ciphertexts of the bits of q0
+        let q0 = &sk.x0 / &sk.p;
+        let bit_keys = (0..q0.bits())
+            .map(|i| {
+                let mut bit = BigPolynomial::new(n);
+                if q0.bit(i) {
+                    bit.coefficients[0] = BigInt::one();
+                }
+                encrypt_vector_with_rng(sk, context, params, &bit, rng)
+            })
+            .collect();
+
+        Self {
+            extraction_key,
+            test_vector_key,
+            bit_keys,
+            x0: sk.x0.clone(),
+            fingerprint: sk.fingerprint(context),
+        }
+    }
+
+    /// Bytes held by each part of the key
+    pub fn footprint(&self) -> KeyFootprint {
+        KeyFootprint {
+            extraction_key: bigint_bytes(self.extraction_key.components.iter().flatten()),
+            test_vector_key: bigint_bytes(
+                self.test_vector_key
+                    .iter()
+                    .flat_map(|c| c.coefficients.iter()),
+            ),
+            bit_keys: bigint_bytes(
+                self.bit_keys
+                    .iter()
+                    .flat_map(|v| v.components.iter())
+                    .flat_map(|c| c.coefficients.iter()),
+            ),
+        }
+    }
+}
+
+/// Bootstrapping key in evaluation form, the vector ciphertexts already in Dcrt form
+///
+/// BootstrapKeyCoefficients keeps the coefficient form, which is what gets stored.
+/// Bootstrapping only ever uses the Dcrt forms of the test vector key and the bit keys, so
+/// converting them once here saves (gamma - eta + 1) * l calls to to_dcrt per bootstrap.
+/// The key is only valid for the DcrtContext it was transformed with, which
+/// BlindRotation::new checks
+pub struct BootstrappingKey {
+    /// Extracts the constant coefficient before the rotation
+    pub extraction_key: ExtractionKey,
+
+    /// Dcrt forms of the scalar ciphertexts of round(p / 2t) * b^i
+    pub test_vector_key: Vec<Dcrt>,
+
+    /// Dcrt forms of the vector ciphertexts of the bits of q0
+    pub bit_keys: Vec<Vec<Dcrt>>,
+
+    /// Public modulus of the ciphertexts
+    pub x0: BigInt,
+
+    /// Fingerprint of the key whose ciphertexts can be bootstrapped
+    pub fingerprint: u64,
+
+    /// The DcrtContext the key was transformed with
+    pub context_id: DcrtContextId,
+}
+
+impl BootstrappingKey {
+    /// Generates the bootstrapping key of a secret key directly in evaluation form
+    pub fn generate<R: Rng + ?Sized>(
+        sk: &GaheSecretKey,
+        context: &GaheContext,
+        dcrt_context: &DcrtContext,
+        params: &FhezParameters,
+        rng: &mut R,
+    ) -> Self {
+        Self::from_coefficients(
+            BootstrapKeyCoefficients::generate(sk, context, params, rng),
+            dcrt_context,
+            params,
+        )
+    }
+
+    /// Transforms a key in coefficient form, which is dropped afterwards
+    ///
+    /// Clone the key first to keep both forms
+    pub fn from_coefficients(
+        key: BootstrapKeyCoefficients,
+        context: &DcrtContext,
+        params: &FhezParameters,
+    ) -> Self {
+        let test_vector_key = key
+            .test_vector_key
+            .iter()
+            .map(|c| to_dcrt(c, context, params))
+            .collect();
+        let bit_keys = key
+            .bit_keys
+            .iter()
+            .map(|v| {
+                v.components
+                    .iter()
+                    .map(|c| to_dcrt(c, context, params))
+                    .collect()
+            })
+            .collect();
+        Self {
+            extraction_key: key.extraction_key,
+            test_vector_key,
+            bit_keys,
+            x0: key.x0,
+            fingerprint: key.fingerprint,
+            context_id: context.id(),
+        }
+    }
+
+    /// Integer ciphertext of the constant coefficient of a scalar ciphertext
+    ///
+    /// The LWE like output c is p * q + e + round(p / t) * m_0 mod x0, where m_0 is the
+    /// constant coefficient of the message and e the noise of that coefficient, plus the
+    /// extraction noise of l * n digits of at most b / 2 against 2^rho
+    pub fn extract(
+        &self,
+        ciphertext: &ScalarCiphertext,
+        params: &FhezParameters,
+    ) -> Result<BigInt, GaheError> {
+        Ok(self.extraction_key.extract(ciphertext, 0, params)?.value)
+    }
+
+    /// Bytes held by each part of the key
+    pub fn footprint(&self) -> KeyFootprint {
+        KeyFootprint {
+            extraction_key: bigint_bytes(self.extraction_key.components.iter().flatten()),
+            test_vector_key: self.test_vector_key.iter().map(Dcrt::size_in_bytes).sum(),
+            bit_keys: self
+                .bit_keys
+                .iter()
+                .flatten()
+                .map(Dcrt::size_in_bytes)
+                .sum(),
+        }
+    }
+}
+
+/// Memory held by the parts of a bootstrapping key, in bytes
+///
+/// Counts the digits of the integers and the values of the Dcrt layers, not the headers
+/// of the vectors around them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyFootprint {
+    pub extraction_key: usize,
+    pub test_vector_key: usize,
+    pub bit_keys: usize,
+}
+
+impl KeyFootprint {
+    pub fn total(&self) -> usize {
+        self.extraction_key + self.test_vector_key + self.bit_keys
+    }
+}
+
+/// Bytes of the magnitudes of some integers
+fn bigint_bytes<'a>(values: impl Iterator<Item = &'a BigInt>) -> usize {
+    values.map(|x| x.bits().div_ceil(8) as usize).sum()
+}
+
+/// Refreshes the constant coefficient of a scalar ciphertext with binary messages
+///
+/// The output is a scalar ciphertext under the same key whose constant coefficient encrypts
+/// the same bit, with a noise that only depends on the bootstrapping key. The other
+/// coefficients of the output carry no meaningful message
+///
+/// Only binary messages are supported, other values of t fail with
+/// GaheError::UnsupportedPlaintextModulus
+///
+/// The context must be the one the key was transformed with, see BlindRotation::new
+pub fn bootstrap(
+    ciphertext: &ScalarCiphertext,
+    key: &BootstrappingKey,
+    context: &DcrtContext,
+    params: &FhezParameters,
+) -> Result<ScalarCiphertext, GaheError> {
+    if params.t != 2 {
+        return Err(GaheError::UnsupportedPlaintextModulus { t: params.t });
+    }
+    let n = params.n;
+    let rotation = BlindRotation::new(key, context, params)?;
+
+    // Integer ciphertext of the constant coefficient and its exponents in Z_2n
+    let extracted = key.extract(ciphertext, params)?;
+    let exponents = rotation_exponents(&extracted, &key.x0, key.bit_keys.len(), n);
+
+    // The rotation by X^(- n / 2) centers the windows: phases of the bit 0 land in [0, n)
+    // and read + 1 from the test polynomial, phases of the bit 1 land in [n, 2n) and read - 1
+    let test_polynomial = BigPolynomial {
+        coefficients: vec![BigInt::one(); n],
+    };
+    let acc = rotation.run(
+        &test_polynomial.mul_by_monomial(-(n as i64 / 2)),
+        &exponents,
+    )?;
+
+    // Maps + round(p / 4) to 0 and - round(p / 4) to - round(p / 2), that is, to the bit 1
+    let mut one = BigPolynomial::new(n);
+    one.coefficients[0] = BigInt::one();
+    let offset = rotation.encode(&one);
+    Ok(ScalarCiphertext::new(
+        &acc.poly - &offset,
+        &key.x0,
+        key.fingerprint,
+    ))
+}
+
+/// Blind rotation of a test polynomial by a secret exponent, the core of the bootstrapping
+///
+/// Given public exponents a_i in Z_2n and the vector ciphertexts of the secret bits s_i in
+/// the bootstrapping key, computes a scalar ciphertext of round(p / 2t) * v * X^(- e) for
+/// e = sum a_i * s_i, so its constant coefficient encrypts the coefficient e of the test
+/// polynomial v, negated when e >= n. Any v works, which makes it a programmable lookup
+/// table: with t = 2, v = 2w encrypts round(p / t) * w * X^(- e) up to |w| of noise
+pub struct BlindRotation<'a> {
+    pub key: &'a BootstrappingKey,
+    context: &'a DcrtContext,
+    params: &'a FhezParameters,
+}
+
+impl<'a> BlindRotation<'a> {
+    /// Fails with ContextMismatch if the key was transformed with another context
+    pub fn new(
+        key: &'a BootstrappingKey,
+        context: &'a DcrtContext,
+        params: &'a FhezParameters,
+    ) -> Result<Self, GaheError> {
+        if key.context_id != context.id() {
+            return Err(GaheError::ContextMismatch);
+        }
+        Ok(Self {
+            key,
+            context,
+            params,
+        })
+    }
+
+    /// The Dcrt ring of the external products
+    fn ring(&self) -> DcrtRing<'a> {
+        DcrtRing {
+            context: self.context,
+            params: self.params,
+        }
+    }
+
+    /// Scalar ciphertext of round(p / 2t) * v from the public test vector key
+    pub fn encode(&self, v: &BigPolynomial) -> BigPolynomial {
+        from_dcrt(&mut self.encode_dcrt(v), self.context, self.params)
+    }
+
+    /// encode in Dcrt form
+    fn encode_dcrt(&self, v: &BigPolynomial) -> Dcrt {
+        let mut v_dcrt = to_dcrt(v, self.context, self.params);
+        let res = external_product(
+            &self.key.test_vector_key,
+            &mut v_dcrt,
+            self.params,
+            &mut self.ring(),
+        );
+        res.rem_euclid(&self.key.x0, self.context, self.params)
+    }
+
+    /// Multiplies an accumulator by X^(- a_i * s_i) for each exponent, one per bit key
+    ///
+    /// Each step is ACC := ACC + BK_i x (X^(- a_i) - 1) * ACC, which adds nothing when the
+    /// bit is 0 and rotates by X^(- a_i) when it is 1. ACC stays in Dcrt form, reduced mod
+    /// x0 after each step with Dcrt::rem_euclid
+    pub fn rotate(&self, mut acc: Dcrt, exponents: &[usize]) -> Result<Dcrt, GaheError> {
+        if exponents.len() != self.key.bit_keys.len() {
+            return Err(GaheError::LengthMismatch {
+                left: exponents.len(),
+                right: self.key.bit_keys.len(),
+            });
+        }
+        for (a_i, bit_key) in exponents.iter().zip(self.key.bit_keys.iter()) {
+            if *a_i == 0 {
+                continue;
+            }
+            let mut rotation = &acc.mul_by_monomial(-(*a_i as i64), self.context) - &acc;
+            let digits =
+                gadget_decompose_dcrt_mod(&mut rotation, &self.key.x0, self.context, self.params);
+            acc = (&acc + &inner_product(bit_key, &digits)).rem_euclid(
+                &self.key.x0,
+                self.context,
+                self.params,
+            );
+        }
+        Ok(acc)
+    }
+
+    /// Encodes the test polynomial and rotates it by X^(- sum a_i * s_i)
+    ///
+    /// The accumulator leaves the Dcrt form only once, at the end
+    pub fn run(
+        &self,
+        test_polynomial: &BigPolynomial,
+        exponents: &[usize],
+    ) -> Result<ScalarCiphertext, GaheError> {
+        let mut acc = self.rotate(self.encode_dcrt(test_polynomial), exponents)?;
+        Ok(ScalarCiphertext::new(
+            from_dcrt(&mut acc, self.context, self.params),
+            &self.key.x0,
+            self.key.fingerprint,
+        ))
+    }
+}
+
+/// Switches an integer ciphertext c to [2^i * c]_x0 mod q for each bit of q0
+///
+/// Since q0 * c / x0 = c / p, the sum of the outputs over the bits s_i of q0 is
+/// q * [c]_p / p mod q up to NoiseModel::mod_switch of rounding noise, so it reads the phase
+/// of c without the secret key other than through the bits
+pub fn mod_switch_bits(c: &BigInt, x0: &BigInt, bit_count: usize, q: &BigInt) -> Vec<BigInt> {
+    (0..bit_count)
+        .map(|i| mod_switch(&(c << i), x0, q))
+        .collect()
+}
+
+/// Exponents a_i := round(2n * [2^i * c]_x0 / x0) mod 2n for each bit of q0
+fn rotation_exponents(c: &BigInt, x0: &BigInt, bit_count: usize, n: usize) -> Vec<usize> {
+    mod_switch_bits(c, x0, bit_count, &BigInt::from(2 * n))
+        .iter()
+        .map(|a_i| a_i.to_usize().expect("a_i is smaller than 2n"))
+        .collect()
+}
@@ -1,12 +1,43 @@
 use crate::prelude::*;
+use rayon::prelude::*;
+use std::sync::Mutex;
 const PI: f64 = std::f64::consts::PI;
 
 /// Represents a polynomial in Double CRT form
-/// Each inner vector Vec Complex f64 represents the polynomial in FFT modulo one of the context primes
+/// Each layer is the transform of the polynomial modulo one of the context primes
 #[derive(Clone, Debug, PartialEq)]
 pub struct Dcrt {
-    pub poly: Vec<Vec<Complex<f64>>>, // poly i is the transform of the polynomial modulo primes i
-    pub n: usize,                     // The degree of the polynomial
+    pub poly: DcrtLayers, // The transforms of the polynomial modulo each prime
+    pub n: usize,         // The degree of the polynomial
+    pub parallel: bool,   // Whether the operations run across the rayon pool, as in its context
+}
+
+/// Transforms of the residues of a polynomial, one layer per prime
+#[derive(Clone, Debug, PartialEq)]
+pub enum DcrtLayers {
+    /// Complex FFTs of the residues twisted by the 2n th roots of unity, rounded back, with
+    /// the primes they are taken modulo
+    Fft {
+        layers: Vec<Vec<Complex<f64>>>,
+        primes: &'static [u32],
+    },
+
+    /// Exact negacyclic NTTs of the residues with the primes they are taken modulo
+    Ntt {
+        layers: Vec<Vec<u64>>,
+        primes: &'static [u32],
+    },
+}
+
+/// Selects how a DcrtContext transforms the residues
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DcrtBackend {
+    /// Floating point FFT, exact while the products stay below the f64 mantissa
+    #[default]
+    Fft,
+
+    /// Number theoretic transform over NTT friendly primes, always exact
+    Ntt,
 }
 
 /// Stores pre computed parameters for Dcrt operations
@@ -16,6 +47,114 @@ pub struct DcrtContext {
     pub m: BigInt,                   // The product M of all primes
     pub m_i: Vec<BigInt>,            // Vector with values m i equals M divided by p i
     pub m_i_inv_mod_pi: Vec<BigInt>, // Vector with the inverse mod equals m i inverse mod p i
+    pub backend: DcrtBackend,        // The transform used for the layers
+    pub n: usize,                    // The degree of the polynomials
+    pub ntt_tables: Vec<NttTable>,   // One table per prime for the NTT backend, empty otherwise
+    pub fft: Option<FftTables>,      // The FFT plan and twiddles for the FFT backend
+    pub parallel: bool,              // Whether the layers are processed across the rayon pool
+    crt_limbs: Option<CrtLimbs>,     // The CRT data in fixed width limbs, if they fit
+}
+
+/// What a Dcrt depends on in its context: the values of one context mean nothing in another
+/// with a different backend, primes or degree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DcrtContextId {
+    pub backend: DcrtBackend,
+    pub primes: &'static [u32],
+    pub n: usize,
+}
+
+/// Pre computed data of the FFT backend, shared by every conversion of a context
+///
+/// The layers use the folding of real negacyclic polynomials: X^n + 1 factors as
+/// (X^(n/2) - i) * (X^(n/2) + i), and a real polynomial is determined by its remainder
+/// mod X^(n/2) - i, the complex polynomial a_lo + i * a_hi of n / 2 coefficients. With
+/// X = psi * Y for psi = e^(i pi / n), so that psi^(n/2) = i, multiplying mod X^(n/2) - i is
+/// a cyclic convolution in Y, so each layer is an n / 2 point FFT of (a_j + i * a_(j + n/2)) * psi^j
+#[derive(Debug)]
+pub struct FftTables {
+    pub plan: Plan,             // The n / 2 point complex FFT
+    pub twiddles: Vec<c64>,     // psi^j = e^(i pi j / n) for j in [0, n / 2)
+    pub psi_powers: Vec<c64>,   // psi^j for j in [0, n), the roots of the monomials
+    pub inv_twiddles: Vec<c64>, // psi^(- j) / (n / 2), also undoing the scaling of the inverse FFT
+    scratch: ScratchPool,
+}
+
+/// Scratch buffers of the FFT plan
+///
+/// Each transform takes a buffer and gives it back, so the conversions only allocate
+/// when more transforms than ever before run at the same time
+struct ScratchPool {
+    buffers: Mutex<Vec<GlobalPodBuffer>>,
+}
+
+impl std::fmt::Debug for ScratchPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self.buffers.lock().map(|b| b.len()).unwrap_or(0);
+        write!(f, "ScratchPool {{ buffers: {count} }}")
+    }
+}
+
+impl FftTables {
+    /// Plans the folded FFT of degree n and computes its twiddles
+    pub fn new(n: usize) -> Self {
+        let half = n / 2;
+        let plan = Plan::new(half, Method::Measure(Duration::from_millis(10)));
+        let psi_powers: Vec<c64> = (0..n)
+            .map(|j| {
+                let theta = PI * j as f64 / n as f64;
+                c64::new(theta.cos(), theta.sin())
+            })
+            .collect();
+        let twiddles = psi_powers[..half].to_vec();
+        let inv_twiddles = twiddles.iter().map(|w| w.conj() / half as f64).collect();
+        Self {
+            plan,
+            twiddles,
+            psi_powers,
+            inv_twiddles,
+            scratch: ScratchPool {
+                buffers: Mutex::new(Vec::new()),
+            },
+        }
+    }
+
+    /// Forward FFT in place
+    pub fn forward(&self, layer: &mut [c64]) {
+        self.with_scratch(|plan, stack| plan.fwd(layer, stack));
+    }
+
+    /// Inverse FFT in place, without the division by n / 2
+    pub fn inverse(&self, layer: &mut [c64]) {
+        self.with_scratch(|plan, stack| plan.inv(layer, stack));
+    }
+
+    /// Multiplies a folded layer by the monomial X^k pointwise, for any k
+    ///
+    /// The forward FFT takes the m th value at e^(- 2 i pi m / (n / 2)) = psi^(- 4m), so the
+    /// value is the polynomial at X = psi^(1 - 4m) and X^k is psi^((1 - 4m) * k)
+    pub fn mul_by_monomial(&self, layer: &mut [c64], k: i64) {
+        let n = self.psi_powers.len();
+        let two_n = 2 * n as i64;
+        let k = k.rem_euclid(two_n);
+        for (m, value) in layer.iter_mut().enumerate() {
+            let e = ((1 - 4 * m as i64) * k).rem_euclid(two_n) as usize;
+            let root = if e < n {
+                self.psi_powers[e]
+            } else {
+                -self.psi_powers[e - n]
+            };
+            *value *= root;
+        }
+    }
+
+    fn with_scratch(&self, transform: impl FnOnce(&Plan, PodStack)) {
+        let reused = self.scratch.buffers.lock().unwrap().pop();
+        let mut buffer =
+            reused.unwrap_or_else(|| GlobalPodBuffer::new(self.plan.fft_scratch().unwrap()));
+        transform(&self.plan, PodStack::new(&mut buffer));
+        self.scratch.buffers.lock().unwrap().push(buffer);
+    }
 }
 
 /// Decomposes a BigPolynomial scalar ciphertext into a vector of l polynomials
@@ -34,10 +173,10 @@ pub fn gadget_decompose(
 
     // Transposes the results to form the output polynomials
     // The i th output polynomial is formed by the i th digit of each original coefficient
-    for i in 0..params.l {
-        for j in 0..params.n {
-            if i < decomposed_coeffs[j].len() {
-                decomposed_polys[i].coefficients[j] = decomposed_coeffs[j][i].clone();
+    for (i, poly) in decomposed_polys.iter_mut().enumerate() {
+        for (j, digits) in decomposed_coeffs.iter().enumerate() {
+            if i < digits.len() {
+                poly.coefficients[j] = digits[i].clone();
             }
         }
     }
@@ -45,13 +184,90 @@ pub fn gadget_decompose(
     decomposed_polys
 }
 
+/// Decomposes a Dcrt scalar ciphertext into the Dcrt forms of its l digit polynomials
+///
+/// Gives the same polynomials as gadget_decompose between from_dcrt and to_dcrt, but each
+/// coefficient is rebuilt by the CRT in fixed width limbs and its signed digits are taken
+/// with shifts, so no BigInt is allocated. Falls back to that BigInt path when b is not a
+/// power of two below 2^63 or the DCRT modulus does not fit in the limbs
+pub fn gadget_decompose_dcrt(
+    a: &mut Dcrt,
+    context: &DcrtContext,
+    params: &FhezParameters,
+) -> Vec<Dcrt> {
+    decompose_dcrt(a, None, context, params)
+}
+
+/// gadget_decompose_dcrt of the coefficients of a reduced to [0, modulus)
+///
+/// The reduction follows the CRT, so a may hold any coefficients below M / 2 in absolute
+/// value, such as an accumulator of external products that was never reduced mod x0
+pub fn gadget_decompose_dcrt_mod(
+    a: &mut Dcrt,
+    modulus: &BigInt,
+    context: &DcrtContext,
+    params: &FhezParameters,
+) -> Vec<Dcrt> {
+    decompose_dcrt(a, Some(modulus), context, params)
+}
+
+/// The decomposition of gadget_decompose_dcrt, reducing the coefficients mod modulus first
+/// if one is given
+fn decompose_dcrt(
+    a: &mut Dcrt,
+    modulus: Option<&BigInt>,
+    context: &DcrtContext,
+    params: &FhezParameters,
+) -> Vec<Dcrt> {
+    let b = params.b;
+    // None if the modulus does not fit in the limbs, Some(None) without modulus
+    let fixed_modulus = match modulus {
+        Some(modulus) => FixedInt::from_bigint(modulus).map(Some),
+        None => Some(None),
+    };
+    let (crt_limbs, fixed_modulus) = match (&context.crt_limbs, fixed_modulus) {
+        (Some(crt_limbs), Some(fixed_modulus)) if b.is_power_of_two() && b < 1 << 63 => {
+            (crt_limbs, fixed_modulus)
+        }
+        _ => {
+            let mut poly = from_dcrt(a, context, params);
+            if let Some(modulus) = modulus {
+                poly = poly % modulus;
+            }
+            return gadget_decompose(&poly, params)
+                .iter()
+                .map(|digits| to_dcrt(digits, context, params))
+                .collect();
+        }
+    };
+    let (residues, _) = inverse_layers(a, context, params);
+    let (n, l) = (params.n, params.l);
+
+    // digits j * l + i is the i th digit of the j th coefficient
+    let mut digits = vec![0i64; n * l];
+    for_each_chunk(context.parallel, &mut digits, l, |j, coefficient_digits| {
+        let mut coefficient = crt_limbs.reconstruct(&residues, j, context.primes);
+        if let Some(modulus) = &fixed_modulus {
+            coefficient = coefficient.rem_euclid(modulus);
+        }
+        coefficient.signed_digits(b, coefficient_digits);
+    });
+
+    map_indices(context.parallel, l, |i| {
+        residues_to_dcrt(context, n, |p| {
+            (0..n)
+                .map(|j| digits[j * l + i].rem_euclid(p as i64) as u64)
+                .collect()
+        })
+    })
+}
+
 /// Reconstructs a coefficient from its congruences using the Chinese Remainder Theorem
 pub fn crt(congruences: &[BigInt], context: &DcrtContext) -> BigInt {
     let mut solution = BigInt::zero();
 
     // The formula is solution equals Sum a i times m i times m i inverse mod p i mod M
-    for i in 0..congruences.len() {
-        let a_i = &congruences[i];
+    for (i, a_i) in congruences.iter().enumerate() {
         let mi = &context.m_i[i];
         let inv = &context.m_i_inv_mod_pi[i];
         solution += a_i * mi * inv;
@@ -60,33 +276,65 @@ pub fn crt(congruences: &[BigInt], context: &DcrtContext) -> BigInt {
     solution % &context.m
 }
 
-/// Converts a BigPolynomial to the Dcrt form
-pub fn to_dcrt(
-    a: &BigPolynomial,
+/// Converts a BigPolynomial to the Dcrt form of the context backend
+pub fn to_dcrt(a: &BigPolynomial, context: &DcrtContext, params: &FhezParameters) -> Dcrt {
+    residues_to_dcrt(context, params.n, |p| {
+        // Uses rem euclid to ensure the remainder is always positive
+        let p_big = BigInt::from(p);
+        a.coefficients
+            .iter()
+            .map(|coef| coef.rem_euclid(&p_big).to_u64().unwrap())
+            .collect()
+    })
+}
+
+/// Transforms the residues of a polynomial of degree n, given for each prime by residues_mod,
+/// to the Dcrt form of the context backend
+fn residues_to_dcrt(
     context: &DcrtContext,
-    plan: &mut Plan,
-    params: &FhezParameters,
+    n: usize,
+    residues_mod: impl Fn(u32) -> Vec<u64> + Sync + Send,
 ) -> Dcrt {
-    let mut res = Dcrt::new(params.n, context.primes.len());
+    let poly = match context.backend {
+        DcrtBackend::Fft => {
+            let tables = context.fft_tables();
+            let half = n / 2;
+            let mut layers = vec![vec![c64::new(0.0, 0.0); half]; context.primes.len()];
 
-    for (i, p_u32) in context.primes.iter().enumerate() {
-        let p_big = p_u32.to_bigint().unwrap();
+            for_each_layer(context.parallel, &mut layers, |i, layer| {
+                let residues = residues_mod(context.primes[i]);
 
-        for (j, coef) in a.coefficients.iter().enumerate() {
-            // Uses rem euclid to ensure the remainder is always positive
-            let reduced_coef = coef.rem_euclid(&p_big);
-
-            let coef_f64 = reduced_coef.to_f64().unwrap_or(0.0);
-
-            let theta = PI * j as f64 / params.n as f64;
-            let twiddle = c64::new(theta.cos(), theta.sin());
-
-            res.poly[i][j] = c64::new(coef_f64, 0.0) * twiddle;
+                // Folds the coefficients j and j + n / 2 into one complex value
+                for (j, (value, twiddle)) in
+                    layer.iter_mut().zip(tables.twiddles.iter()).enumerate()
+                {
+                    *value = c64::new(residues[j] as f64, residues[j + half] as f64) * twiddle;
+                }
+                // Applies FFT
+                tables.forward(layer);
+            });
+            DcrtLayers::Fft {
+                layers,
+                primes: context.primes,
+            }
         }
-        // Applies FFT
-        to_fft(&mut res.poly[i], plan);
+        DcrtBackend::Ntt => {
+            let layers = map_indices(context.parallel, context.ntt_tables.len(), |i| {
+                let mut layer = residues_mod(context.primes[i]);
+                context.ntt_tables[i].forward(&mut layer);
+                layer
+            });
+            DcrtLayers::Ntt {
+                layers,
+                primes: context.primes,
+            }
+        }
+    };
+    Dcrt {
+        poly,
+        n,
+        parallel: context.parallel,
     }
-    res
 }
 
 /// Converts a Dcrt back to a BigPolynomial
@@ -95,40 +343,233 @@ pub fn to_dcrt(
 /// of the polynomial reduced modulo the i th prime For the CRT of the j th coefficient
 /// simply collect a poly 0 j a poly 1 j a poly k minus 1 j directly
 /// without needing to transpose the entire matrix
-pub fn from_dcrt(
+///
+/// The FFT values are rounded without any check, see from_dcrt_checked
+pub fn from_dcrt(a: &mut Dcrt, context: &DcrtContext, params: &FhezParameters) -> BigPolynomial {
+    let (residues, _) = inverse_layers(a, context, params);
+    crt_coefficients(&residues, context, params)
+}
+
+/// Converts a Dcrt back to a BigPolynomial, failing if the FFT lost precision
+///
+/// Every real and imaginary part of the inverse FFT layers must be within threshold of an
+/// integer, otherwise its rounding may be wrong. The report of the measured error is returned
+/// with the polynomial. NTT layers are always exact
+pub fn from_dcrt_checked(
     a: &mut Dcrt,
     context: &DcrtContext,
-    plan: &mut Plan,
     params: &FhezParameters,
-) -> BigPolynomial {
-    // Applies inverse FFT and undoes the twiddle for each prime layer
-    for poly_mod_p in a.poly.iter_mut() {
-        from_fft(poly_mod_p, plan, params);
-
-        for j in 0..params.n {
-            let theta = PI * j as f64 / params.n_f64();
-            let inv_twiddle = c64::new(theta.cos(), -theta.sin()); // Conjugate
-            poly_mod_p[j] *= inv_twiddle;
+    threshold: f64,
+) -> Result<(BigPolynomial, PrecisionReport), DcrtError> {
+    let (residues, report) = inverse_layers(a, context, params);
+    if !report.within(threshold) {
+        return Err(DcrtError::PrecisionLoss {
+            rounding_error: report.max_rounding_error,
+            threshold,
+        });
+    }
+    Ok((crt_coefficients(&residues, context, params), report))
+}
+
+/// Largest error seen while rounding the inverse FFT layers of a Dcrt
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PrecisionReport {
+    pub max_rounding_error: f64, // Largest distance of a real or imaginary part from its nearest integer
+}
+
+impl PrecisionReport {
+    /// Whether the error does not exceed the threshold
+    pub fn within(&self, threshold: f64) -> bool {
+        self.max_rounding_error <= threshold
+    }
+}
+
+/// Threshold of from_dcrt_checked that keeps a margin below the 0.5 where rounding fails
+pub const DEFAULT_PRECISION_THRESHOLD: f64 = 0.25;
+
+/// Errors of the checked Dcrt conversions
+#[derive(Debug, Clone, PartialEq)]
+pub enum DcrtError {
+    /// An inverse FFT value is too far from an integer to be rounded safely,
+    /// infinite if a value is not finite
+    PrecisionLoss { rounding_error: f64, threshold: f64 },
+}
+
+impl std::fmt::Display for DcrtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DcrtError::PrecisionLoss {
+                rounding_error,
+                threshold,
+            } => write!(
+                f,
+                "FFT precision loss: rounding error {rounding_error:e} against the threshold \
+                {threshold:e}"
+            ),
         }
     }
+}
+
+impl std::error::Error for DcrtError {}
+
+/// Heuristic estimate of the rounding error of a sum of products computed with the FFT
+///
+/// The residues are below the prime, so norm_a and norm_b are at most p, and the exact
+/// coefficients reach magnitude = terms * n * norm_a * norm_b, known up to magnitude * 2^-52.
+/// The errors of the butterflies are assumed to cancel, as they do on random residues, so
+/// this is not a bound: only from_dcrt_checked tells whether a conversion was exact
+pub fn fft_error_estimate(n: usize, prime: u32, norm_a: f64, norm_b: f64, terms: usize) -> f64 {
+    let p = prime as f64;
+    let magnitude = terms as f64 * n as f64 * norm_a.min(p) * norm_b.min(p);
+    magnitude * f64::EPSILON
+}
 
+/// Largest FFT value whose rounding can be checked, 2^52
+const MAX_EXACT_FFT_VALUE: f64 = (1u64 << 52) as f64;
+
+/// Undoes the transforms of each layer, giving integers congruent to each coefficient modulo
+/// each prime, res i j for the i th prime, and the rounding errors of the FFT layers
+///
+/// Above MAX_EXACT_FFT_VALUE every f64 is an integer, so the distance to the nearest
+/// integer says nothing about the error, and such values count as an infinite rounding error
+fn inverse_layers(
+    a: &mut Dcrt,
+    context: &DcrtContext,
+    params: &FhezParameters,
+) -> (Vec<Vec<i64>>, PrecisionReport) {
+    let mut report = PrecisionReport::default();
+    let residues = match &mut a.poly {
+        DcrtLayers::Fft { layers, .. } => {
+            // Applies inverse FFT and undoes the twiddle for each prime layer
+            let tables = context.fft_tables();
+            for_each_layer(context.parallel, layers, |_, poly_mod_p| {
+                tables.inverse(poly_mod_p);
+
+                for (coeff, inv_twiddle) in poly_mod_p.iter_mut().zip(tables.inv_twiddles.iter()) {
+                    *coeff *= inv_twiddle;
+                }
+            });
+
+            // Unfolds the real parts into the first half and the imaginary parts into the second
+            let rounded = map_indices(context.parallel, layers.len(), |i| {
+                let mut max_rounding_error: f64 = 0.0;
+                let mut round = |x: f64| -> i64 {
+                    let rounded = x.round();
+                    let rounding_error = if rounded.abs() <= MAX_EXACT_FFT_VALUE {
+                        (x - rounded).abs()
+                    } else {
+                        f64::INFINITY
+                    };
+                    max_rounding_error = max_rounding_error.max(rounding_error);
+                    rounded as i64
+                };
+                let low: Vec<i64> = layers[i].iter().map(|c| round(c.re)).collect();
+                let high: Vec<i64> = layers[i].iter().map(|c| round(c.im)).collect();
+                ([low, high].concat(), max_rounding_error)
+            });
+            rounded
+                .into_iter()
+                .map(|(residues, rounding_error)| {
+                    report.max_rounding_error = report.max_rounding_error.max(rounding_error);
+                    residues
+                })
+                .collect()
+        }
+        DcrtLayers::Ntt { layers, .. } => {
+            assert_eq!(
+                context.backend,
+                DcrtBackend::Ntt,
+                "The Dcrt was not computed with an NTT context"
+            );
+            for_each_layer(context.parallel, layers, |i, layer| {
+                context.ntt_tables[i].inverse(layer)
+            });
+            layers
+                .iter()
+                .map(|layer| layer.iter().map(|&c| c as i64).collect())
+                .collect()
+        }
+    };
+    (residues, report)
+}
+
+/// Reconstructs the coefficients from their residues with the CRT, centered mod M
+fn crt_coefficients(
+    residues: &[Vec<i64>],
+    context: &DcrtContext,
+    params: &FhezParameters,
+) -> BigPolynomial {
     let m_half = &context.m >> 1;
-    let num_primes = context.primes.len();
 
-    let mut res = BigPolynomial::new(params.n);
-    for j in 0..params.n {
-        // Congruence of the j th coefficient in each prime round a poly i j re
-        let congruences: Vec<BigInt> = (0..num_primes)
-            .map(|i| BigInt::from_f64(a.poly[i][j].re.round()).unwrap_or_else(BigInt::zero))
+    let coefficients = map_indices(context.parallel, params.n, |j| {
+        // Congruence of the j th coefficient in each prime
+        let congruences: Vec<BigInt> = residues
+            .iter()
+            .map(|layer| BigInt::from(layer[j]))
             .collect();
 
         // Reconstructs the original coefficient via CRT and centers it in negative M 2 M 2
         let crt_result = crt(&congruences, context);
         let shifted: BigInt = crt_result + &m_half;
         let remainder = shifted.rem_euclid(&context.m);
-        res.coefficients[j] = remainder - &m_half;
+        remainder - &m_half
+    });
+    BigPolynomial { coefficients }
+}
+
+/// Maps every index in 0..count, across the rayon pool when parallel is set
+///
+/// The results are collected in order, so both paths return the same vector
+fn map_indices<R: Send>(
+    parallel: bool,
+    count: usize,
+    op: impl Fn(usize) -> R + Sync + Send,
+) -> Vec<R> {
+    if parallel {
+        (0..count).into_par_iter().map(op).collect()
+    } else {
+        (0..count).map(op).collect()
+    }
+}
+
+/// Applies op to every layer with its index, across the rayon pool when parallel is set
+fn for_each_layer<T: Send>(
+    parallel: bool,
+    layers: &mut [T],
+    op: impl Fn(usize, &mut T) + Sync + Send,
+) {
+    if parallel {
+        layers
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, layer)| op(i, layer));
+    } else {
+        layers
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, layer)| op(i, layer));
+    }
+}
+
+/// Applies op to every chunk of size values with its index, across the rayon pool when
+/// parallel is set
+fn for_each_chunk<T: Send>(
+    parallel: bool,
+    values: &mut [T],
+    size: usize,
+    op: impl Fn(usize, &mut [T]) + Sync + Send,
+) {
+    if parallel {
+        values
+            .par_chunks_mut(size)
+            .enumerate()
+            .for_each(|(i, chunk)| op(i, chunk));
+    } else {
+        values
+            .chunks_mut(size)
+            .enumerate()
+            .for_each(|(i, chunk)| op(i, chunk));
     }
-    res
 }
 
 /// Computes the dot product inner product of two vectors of DCRT polynomials
@@ -145,154 +586,568 @@ pub fn inner_product(a: &[Dcrt], b: &[Dcrt]) -> Dcrt {
         panic!("Cannot compute inner product of empty vectors");
     }
 
-    let mut res = &a[0] * &b[0];
-
-    for (poly_a, poly_b) in a.iter().zip(b.iter()).skip(1) {
-        res += &*poly_a * &*poly_b;
+    // The products run in parallel, but are always added in the same order
+    let mut products = map_indices(a[0].parallel, a.len(), |i| &a[i] * &b[i]).into_iter();
+    let mut res = products.next().unwrap();
+    for product in products {
+        res += product;
     }
 
     res
 }
 
 /// Computes the external product homomorphic mixed product between a ciphertext vector
-/// and a scalar ciphertext both in DCRT format
-pub fn external_product(
-    vector_ciphertext: &[Dcrt],
-    scalar_ciphertext: &mut Dcrt,
-    context: &DcrtContext,
-    plan: &mut Plan,
+/// and a scalar ciphertext both in the representation R
+///
+/// The scalar ciphertext is decomposed with PolyRing::gadget_decompose, so a Dcrt one never
+/// leaves the DCRT domain and the vector ciphertext stays in Dcrt form between calls, which
+/// is what the bootstrapping keys need
+pub fn external_product<R: PolyRing>(
+    vector_ciphertext: &[R],
+    scalar_ciphertext: &mut R,
     params: &FhezParameters,
-) -> Dcrt {
-    // Converts the scalar ciphertext from the DCRT FFT domain back to the integer domain
-    // to perform the exact decomposition
-    let scalar_poly = from_dcrt(scalar_ciphertext, context, plan, params);
-
-    // Decomposes the scalar BigPolynomial into a vector of l BigPolynomials
-    let decomposed_scalar_polys = gadget_decompose(&scalar_poly, params);
-
-    // Converts each of the decomposed polynomials back to DCRT format
-    let decomposed_scalar_dcrt: Vec<Dcrt> = decomposed_scalar_polys
-        .iter()
-        .map(|p| to_dcrt(p, context, plan, params))
-        .collect();
+    ring: &mut R::Context<'_>,
+) -> R {
+    // Decomposes the scalar ciphertext into l polynomials of the same representation
+    let decomposed_scalar = scalar_ciphertext.gadget_decompose(params, ring);
 
     // Computes the inner product between the original vector ciphertext and
     // the decomposed vector of the scalar ciphertext
-    inner_product(vector_ciphertext, &decomposed_scalar_dcrt)
+    R::inner_product(vector_ciphertext, &decomposed_scalar)
+}
+
+/// The CRT data of a context in COEFFICIENT_LIMBS limbs, enough for DCRT moduli of up to
+/// 500 bits
+#[derive(Debug)]
+struct CrtLimbs {
+    m: FixedInt<COEFFICIENT_LIMBS>,        // The product M of the primes
+    m_half: FixedInt<COEFFICIENT_LIMBS>,   // (M - 1) / 2, the largest centered value
+    m_i: Vec<FixedInt<COEFFICIENT_LIMBS>>, // M divided by p i
+    m_i_inv: Vec<u64>,                     // m i inverse mod p i
+}
+
+impl CrtLimbs {
+    /// Converts the CRT data of a context, or None if the sums of the CRT do not fit
+    fn new(m: &BigInt, m_i: &[BigInt], m_i_inv_mod_pi: &[BigInt]) -> Option<Self> {
+        // Sum y_i * m_i is below 256 * M for up to 256 primes, and the sign needs one bit
+        if m.bits() + 9 > FixedInt::<COEFFICIENT_LIMBS>::BITS as u64 || m_i.len() > 256 {
+            return None;
+        }
+        let to_fixed = |x: &BigInt| FixedInt::from_bigint(x).expect("M fits in the limbs");
+        Some(Self {
+            m: to_fixed(m),
+            m_half: to_fixed(&(m >> 1)),
+            m_i: m_i.iter().map(to_fixed).collect(),
+            m_i_inv: m_i_inv_mod_pi
+                .iter()
+                .map(|inv| inv.to_u64().unwrap())
+                .collect(),
+        })
+    }
+
+    /// The j th coefficient from its residues, centered in (- M / 2, M / 2] like from_dcrt
+    fn reconstruct(
+        &self,
+        residues: &[Vec<i64>],
+        j: usize,
+        primes: &[u32],
+    ) -> FixedInt<COEFFICIENT_LIMBS> {
+        let mut x = FixedInt::ZERO;
+        let mut quotient = 0.0;
+        for (i, &p) in primes.iter().enumerate() {
+            let p = p as u64;
+            let y = mul_mod(
+                residues[i][j].rem_euclid(p as i64) as u64,
+                self.m_i_inv[i],
+                p,
+            );
+            x += self.m_i[i].mul_u64(y);
+            quotient += y as f64 / p as f64;
+        }
+
+        // Sum y_i * m_i = M * sum y_i / p_i, so the floor of the quotient is the multiple of
+        // M to remove, off by at most one from the rounding of the f64 sum
+        x -= self.m.mul_u64(quotient.floor() as u64);
+        if x.is_negative() {
+            x += self.m;
+        } else if x >= self.m {
+            x -= self.m;
+        }
+
+        if x > self.m_half {
+            x -= self.m;
+        }
+        x
+    }
 }
 
 impl Dcrt {
-    /// Creates a new null Dcrt polynomial
-    pub fn new(n: usize, prime_count: usize) -> Self {
+    /// Creates a new null Dcrt polynomial, with folded layers of n / 2 values
+    pub fn new(n: usize, primes: &'static [u32]) -> Self {
         Self {
-            poly: vec![vec![c64::new(0.0, 0.0); n]; prime_count],
+            poly: DcrtLayers::Fft {
+                layers: vec![vec![c64::new(0.0, 0.0); n / 2]; primes.len()],
+                primes,
+            },
             n,
+            parallel: false,
         }
     }
-}
 
-impl<'a, 'b> Add<&'b Dcrt> for &'a Dcrt {
-    type Output = Dcrt;
+    /// Creates a new null Dcrt polynomial in NTT form
+    pub fn new_ntt(n: usize, primes: &'static [u32]) -> Self {
+        Self {
+            poly: DcrtLayers::Ntt {
+                layers: vec![vec![0; n]; primes.len()],
+                primes,
+            },
+            n,
+            parallel: false,
+        }
+    }
 
-    /// Adds two Dcrt polynomials returning a new one
-    fn add(self, rhs: &'b Dcrt) -> Self::Output {
+    /// Number of prime layers
+    pub fn prime_count(&self) -> usize {
+        match &self.poly {
+            DcrtLayers::Fft { layers, .. } => layers.len(),
+            DcrtLayers::Ntt { layers, .. } => layers.len(),
+        }
+    }
+
+    /// Bytes held by the values of the layers
+    pub fn size_in_bytes(&self) -> usize {
+        match &self.poly {
+            DcrtLayers::Fft { layers, .. } => layers
+                .iter()
+                .map(|layer| std::mem::size_of_val(layer.as_slice()))
+                .sum(),
+            DcrtLayers::Ntt { layers, .. } => layers
+                .iter()
+                .map(|layer| std::mem::size_of_val(layer.as_slice()))
+                .sum(),
+        }
+    }
+
+    /// Dcrt form of the monomial c * X^k, reduced with X^n = - 1 for k >= n
+    ///
+    /// Multiplying by it multiplies a polynomial by the constant without leaving the Dcrt form
+    pub fn monomial(c: i64, k: usize, context: &DcrtContext, params: &FhezParameters) -> Self {
+        let n = params.n;
+        // The sign is applied in BigInt, since - c overflows for i64::MIN
+        let c = BigInt::from(c);
+        let mut poly = BigPolynomial::new(n);
+        poly.coefficients[k % n] = if (k / n).is_multiple_of(2) { c } else { -c };
+        to_dcrt(&poly, context, params)
+    }
+
+    /// Multiplies by the monomial X^k, for any k, without leaving the Dcrt form
+    ///
+    /// Each value is multiplied by the matching power of the 2n th root of unity of its
+    /// layer, in O(n) against the O(n log n) of a product with Dcrt::monomial
+    pub fn mul_by_monomial(&self, k: i64, context: &DcrtContext) -> Dcrt {
+        let mut res = self.clone();
+        match &mut res.poly {
+            DcrtLayers::Fft { layers, .. } => {
+                let tables = context.fft_tables();
+                for_each_layer(self.parallel, layers, |_, layer| {
+                    tables.mul_by_monomial(layer, k)
+                });
+            }
+            DcrtLayers::Ntt { layers, .. } => {
+                assert_eq!(
+                    context.backend,
+                    DcrtBackend::Ntt,
+                    "The Dcrt was not computed with an NTT context"
+                );
+                for_each_layer(self.parallel, layers, |i, layer| {
+                    context.ntt_tables[i].mul_by_monomial(layer, k)
+                });
+            }
+        }
+        res
+    }
+
+    /// Reduces every coefficient to [0, modulus) without leaving the Dcrt form
+    ///
+    /// The coefficients are rebuilt by the CRT in fixed width limbs like in
+    /// gadget_decompose_dcrt, falling back to from_dcrt and to_dcrt when the DCRT modulus or
+    /// the modulus do not fit in the limbs. The FFT values are rounded on the way, so their
+    /// error does not add up over a chain of external products
+    pub fn rem_euclid(
+        &self,
+        modulus: &BigInt,
+        context: &DcrtContext,
+        params: &FhezParameters,
+    ) -> Dcrt {
+        let (crt_limbs, fixed_modulus) = match (&context.crt_limbs, FixedInt::from_bigint(modulus))
+        {
+            (Some(crt_limbs), Some(fixed_modulus)) => (crt_limbs, fixed_modulus),
+            _ => {
+                let poly = from_dcrt(&mut self.clone(), context, params) % modulus;
+                return to_dcrt(&poly, context, params);
+            }
+        };
+        let (residues, _) = inverse_layers(&mut self.clone(), context, params);
+        let coefficients = map_indices(context.parallel, self.n, |j| {
+            crt_limbs
+                .reconstruct(&residues, j, context.primes)
+                .rem_euclid(&fixed_modulus)
+        });
+        residues_to_dcrt(context, self.n, |p| {
+            coefficients.iter().map(|c| c.rem_u64(p as u64)).collect()
+        })
+    }
+
+    /// Multiplies every coefficient by the integer a, exactly in both backends
+    ///
+    /// The NTT values are multiplied by the residues of a. The FFT values only hold the
+    /// residues up to the f64 precision, which a factor up to p_i would exhaust, so the
+    /// layers are rounded back to residues, multiplied by a mod p_i and transformed again.
+    /// The result is exact while the products fit in the modulus M of the context
+    pub fn mul_integer(&self, a: &BigInt, context: &DcrtContext, params: &FhezParameters) -> Dcrt {
+        let residue = |p: u32| a.rem_euclid(&BigInt::from(p)).to_u64().unwrap();
+        match &self.poly {
+            DcrtLayers::Fft { .. } => {
+                let (residues, _) = inverse_layers(&mut self.clone(), context, params);
+                residues_to_dcrt(context, self.n, |p| {
+                    let i = context.primes.iter().position(|&q| q == p).unwrap();
+                    let a_i = residue(p);
+                    let p = p as u64;
+                    residues[i]
+                        .iter()
+                        .map(|&c| mul_mod(c.rem_euclid(p as i64) as u64, a_i, p))
+                        .collect()
+                })
+            }
+            DcrtLayers::Ntt { layers, primes } => {
+                let layers = layers
+                    .iter()
+                    .zip(primes.iter())
+                    .map(|(layer, &p)| {
+                        let a_i = residue(p);
+                        layer.iter().map(|&c| mul_mod(c, a_i, p as u64)).collect()
+                    })
+                    .collect();
+                Dcrt {
+                    poly: DcrtLayers::Ntt { layers, primes },
+                    n: self.n,
+                    parallel: self.parallel,
+                }
+            }
+        }
+    }
+
+    /// Replaces every value pointwise, with fft_op for FFT layers and ntt_op for NTT
+    /// layers, both receiving the prime of the layer
+    fn map_apply(
+        &mut self,
+        fft_op: impl Fn(&mut c64, u64) + Sync + Send,
+        ntt_op: impl Fn(u64, u64) -> u64 + Sync + Send,
+    ) {
+        match &mut self.poly {
+            DcrtLayers::Fft { layers, primes } => {
+                let primes = *primes;
+                for_each_layer(self.parallel, layers, |i, layer| {
+                    let p = primes[i] as u64;
+                    for a in layer.iter_mut() {
+                        fft_op(a, p);
+                    }
+                });
+            }
+            DcrtLayers::Ntt { layers, primes } => {
+                let primes = *primes;
+                for_each_layer(self.parallel, layers, |i, layer| {
+                    let p = primes[i] as u64;
+                    for a in layer.iter_mut() {
+                        *a = ntt_op(*a, p);
+                    }
+                });
+            }
+        }
+    }
+
+    /// Combines the values of rhs into self pointwise, with fft_op for FFT layers and
+    /// ntt_op, which receives the prime, for NTT layers
+    ///
+    /// The layers are processed across the rayon pool if self is parallel
+    fn zip_apply(
+        &mut self,
+        rhs: &Dcrt,
+        fft_op: impl Fn(&mut c64, c64) + Sync + Send,
+        ntt_op: impl Fn(u64, u64, u64) -> u64 + Sync + Send,
+    ) {
         assert_eq!(
-            self.poly.len(),
-            rhs.poly.len(),
+            std::mem::discriminant(&self.poly),
+            std::mem::discriminant(&rhs.poly),
+            "Mismatch in the Dcrt backends"
+        );
+        assert_eq!(
+            self.prime_count(),
+            rhs.prime_count(),
             "Mismatch in the number of primes"
         );
-        let mut res = Dcrt::new(self.n, self.poly.len());
-        for i in 0..self.poly.len() {
-            for j in 0..self.n {
-                res.poly[i][j] = self.poly[i][j] + rhs.poly[i][j];
+        match (&mut self.poly, &rhs.poly) {
+            (DcrtLayers::Fft { layers: left, .. }, DcrtLayers::Fft { layers: right, .. }) => {
+                for_each_layer(self.parallel, left, |i, layer| {
+                    for (a, b) in layer.iter_mut().zip(right[i].iter()) {
+                        fft_op(a, *b);
+                    }
+                });
+            }
+            (
+                DcrtLayers::Ntt { layers, primes },
+                DcrtLayers::Ntt {
+                    layers: rhs_layers, ..
+                },
+            ) => {
+                let primes = *primes;
+                for_each_layer(self.parallel, layers, |i, layer| {
+                    let p = primes[i] as u64;
+                    for (a, b) in layer.iter_mut().zip(rhs_layers[i].iter()) {
+                        *a = ntt_op(*a, *b, p);
+                    }
+                });
             }
+            _ => unreachable!(),
         }
+    }
+}
+
+// Adição
+impl Add for Dcrt {
+    type Output = Dcrt;
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl<'a> Add<&'a Dcrt> for Dcrt {
+    type Output = Dcrt;
+    fn add(self, rhs: &'a Dcrt) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<Dcrt> for &Dcrt {
+    type Output = Dcrt;
+    fn add(self, rhs: Dcrt) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl<'b> Add<&'b Dcrt> for &Dcrt {
+    type Output = Dcrt;
+
+    /// Adds two Dcrt polynomials returning a new one
+    fn add(self, rhs: &'b Dcrt) -> Self::Output {
+        let mut res = self.clone();
+        res.zip_apply(rhs, |a, b| *a += b, add_mod);
         res
     }
 }
 
 impl AddAssign for Dcrt {
-    /// Adds another Dcrt polynomial to this one in place
     fn add_assign(&mut self, rhs: Self) {
-        assert_eq!(
-            self.poly.len(),
-            rhs.poly.len(),
-            "Mismatch in the number of primes"
-        );
-        for i in 0..self.poly.len() {
-            for j in 0..self.n {
-                self.poly[i][j] += rhs.poly[i][j];
-            }
-        }
+        *self += &rhs;
+    }
+}
+
+impl<'a> AddAssign<&'a Dcrt> for Dcrt {
+    /// Adds another Dcrt polynomial to this one in place
+    fn add_assign(&mut self, rhs: &'a Dcrt) {
+        self.zip_apply(rhs, |a, b| *a += b, add_mod);
     }
 }
 
-impl<'a, 'b> Mul<&'b Dcrt> for &'a Dcrt {
+// Subtração
+impl Sub for Dcrt {
+    type Output = Dcrt;
+    fn sub(self, rhs: Self) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl<'a> Sub<&'a Dcrt> for Dcrt {
+    type Output = Dcrt;
+    fn sub(self, rhs: &'a Dcrt) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl Sub<Dcrt> for &Dcrt {
+    type Output = Dcrt;
+    fn sub(self, rhs: Dcrt) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl<'b> Sub<&'b Dcrt> for &Dcrt {
+    type Output = Dcrt;
+
+    /// Subtracts two Dcrt polynomials returning a new one
+    fn sub(self, rhs: &'b Dcrt) -> Self::Output {
+        let mut res = self.clone();
+        res.zip_apply(rhs, |a, b| *a -= b, sub_mod);
+        res
+    }
+}
+
+impl SubAssign for Dcrt {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self -= &rhs;
+    }
+}
+
+impl<'a> SubAssign<&'a Dcrt> for Dcrt {
+    /// Subtracts another Dcrt polynomial from this one in place
+    fn sub_assign(&mut self, rhs: &'a Dcrt) {
+        self.zip_apply(rhs, |a, b| *a -= b, sub_mod);
+    }
+}
+
+// Multiplicação (Dcrt * Dcrt)
+impl Mul for Dcrt {
+    type Output = Dcrt;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl<'a> Mul<&'a Dcrt> for Dcrt {
+    type Output = Dcrt;
+    fn mul(self, rhs: &'a Dcrt) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<Dcrt> for &Dcrt {
+    type Output = Dcrt;
+    fn mul(self, rhs: Dcrt) -> Self::Output {
+        self * &rhs
+    }
+}
+
+impl<'b> Mul<&'b Dcrt> for &Dcrt {
     type Output = Dcrt;
 
     /// Multiplies component by component two Dcrt polynomials returning a new one
     fn mul(self, rhs: &'b Dcrt) -> Self::Output {
-        assert_eq!(
-            self.poly.len(),
-            rhs.poly.len(),
-            "Mismatch in the number of primes"
-        );
-        let mut res = Dcrt::new(self.n, self.poly.len());
-        for i in 0..self.poly.len() {
-            for j in 0..self.n {
-                res.poly[i][j] = self.poly[i][j] * rhs.poly[i][j];
-            }
-        }
+        let mut res = self.clone();
+        res.zip_apply(rhs, |a, b| *a *= b, mul_mod);
         res
     }
 }
 
 impl MulAssign for Dcrt {
-    /// Multiplies another Dcrt polynomial to this one in place
     fn mul_assign(&mut self, rhs: Self) {
-        assert_eq!(
-            self.poly.len(),
-            rhs.poly.len(),
-            "Mismatch in the number of primes"
+        *self *= &rhs;
+    }
+}
+
+impl<'a> MulAssign<&'a Dcrt> for Dcrt {
+    /// Multiplies another Dcrt polynomial to this one in place
+    fn mul_assign(&mut self, rhs: &'a Dcrt) {
+        self.zip_apply(rhs, |a, b| *a *= b, mul_mod);
+    }
+}
+
+// Negação
+impl Neg for Dcrt {
+    type Output = Dcrt;
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
+impl Neg for &Dcrt {
+    type Output = Dcrt;
+
+    /// Negates every value, the transform being linear
+    fn neg(self) -> Self::Output {
+        let mut res = self.clone();
+        res.map_apply(|a, _| *a = -*a, |a, p| sub_mod(0, a, p));
+        res
+    }
+}
+
+// Multiplicação (Dcrt * inteiro)
+impl Mul<i64> for Dcrt {
+    type Output = Dcrt;
+    fn mul(self, rhs: i64) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<i64> for &Dcrt {
+    type Output = Dcrt;
+
+    /// Multiplies every coefficient by an integer constant
+    ///
+    /// The constant is reduced mod each prime: the NTT values are multiplied by its residues
+    /// and the FFT values by its centered residues, at most p_i / 2, so the FFT layers stay
+    /// as precise as a product of two Dcrt, see Dcrt::mul_integer for larger operands
+    fn mul(self, rhs: i64) -> Self::Output {
+        let mut res = self.clone();
+        res.map_apply(
+            |a, p| {
+                let residue = rhs.rem_euclid(p as i64);
+                let centered = if 2 * residue > p as i64 {
+                    residue - p as i64
+                } else {
+                    residue
+                };
+                *a *= centered as f64
+            },
+            |a, p| mul_mod(a, rhs.rem_euclid(p as i64) as u64, p),
         );
-        for i in 0..self.poly.len() {
-            for j in 0..self.n {
-                self.poly[i][j] *= rhs.poly[i][j];
-            }
-        }
+        res
+    }
+}
+
+impl MulAssign<i64> for Dcrt {
+    fn mul_assign(&mut self, rhs: i64) {
+        *self = &*self * rhs;
     }
 }
 
 impl DcrtContext {
     /// Creates a new Dcrt context from a list of primes and security parameters
-    pub fn new(params: &FhezParameters) -> Self {
-        // Calculation of required size
-        let size = params.gamma
-            + f64::ceil(f64::log2(params.l_f64()))
-            + f64::log2(params.b_f64())
-            + f64::log2(params.n_f64());
-        let size_bits = size.ceil() as u64;
+    ///
+    /// Fails if the parameters do not pass FhezParameters::validate
+    pub fn new(params: &FhezParameters) -> Result<Self, ParamError> {
+        Self::with_backend(params, DcrtBackend::Fft)
+    }
+
+    /// Creates a new Dcrt context with the chosen backend
+    ///
+    /// The FFT backend uses the primes of the parameters and needs FhezParameters::validate,
+    /// the NTT backend uses NTT_PRIMES_30 and only needs FhezParameters::validate_scheme
+    pub fn with_backend(params: &FhezParameters, backend: DcrtBackend) -> Result<Self, ParamError> {
+        let primes = match backend {
+            DcrtBackend::Fft => {
+                params.validate()?;
+                params.primes
+            }
+            DcrtBackend::Ntt => {
+                params.validate_scheme()?;
+                params.validate_primes(NTT_PRIMES_30)?;
+                NTT_PRIMES_30
+            }
+        };
+        let size_bits = params.dcrt_modulus_bits();
 
         // Selects the minimum amount of primes from the provided list
         let mut m = BigInt::one();
-        let mut primes_to_use = Vec::new();
-        for i in 0..params.primes.len() {
+        let mut prime_count = 0;
+        for &prime in primes {
             if m.bits() >= size_bits {
                 break;
             }
-            m *= params.primes[i];
-            primes_to_use.push(i);
+            m *= prime;
+            prime_count += 1;
         }
 
-        if m.bits() < size_bits {
-            panic!(
-                "The provided prime list is too small for the calculated size of {} \
-                Consider adding more primes",
-                size_bits
-            );
-        }
-
-        let selected_primes: &'static [u32] = &params.primes[..primes_to_use.len()];
+        let selected_primes: &'static [u32] = &primes[..prime_count];
 
         // Calculation of m i and its inverses
         let mut m_i = Vec::with_capacity(selected_primes.len());
@@ -311,11 +1166,61 @@ impl DcrtContext {
             m_i_inv_mod_pi.push(inv);
         }
 
-        Self {
+        let ntt_tables = match backend {
+            DcrtBackend::Fft => Vec::new(),
+            DcrtBackend::Ntt => selected_primes
+                .iter()
+                .map(|&prime| {
+                    NttTable::new(prime, params.n)
+                        .ok_or(ParamError::NoNttRoot { n: params.n, prime })
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        let fft = match backend {
+            DcrtBackend::Fft => Some(FftTables::new(params.n)),
+            DcrtBackend::Ntt => None,
+        };
+
+        let crt_limbs = CrtLimbs::new(&m, &m_i, &m_i_inv_mod_pi);
+
+        Ok(Self {
             primes: selected_primes,
             m,
             m_i,
             m_i_inv_mod_pi,
+            backend,
+            n: params.n,
+            ntt_tables,
+            fft,
+            parallel: false,
+            crt_limbs,
+        })
+    }
+
+    /// Processes the prime layers, the CRT of the coefficients and the decomposed
+    /// polynomials of external products across the rayon pool
+    ///
+    /// The Dcrt polynomials converted by the context inherit the mode, and the results are
+    /// the same as the serial path since every sum is still taken in the same order
+    pub fn with_parallelism(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Identifies the transforms of this context
+    pub fn id(&self) -> DcrtContextId {
+        DcrtContextId {
+            backend: self.backend,
+            primes: self.primes,
+            n: self.n,
         }
     }
+
+    /// The FFT tables, which exist for every context of the FFT backend
+    fn fft_tables(&self) -> &FftTables {
+        self.fft
+            .as_ref()
+            .expect("The Dcrt was not computed with an FFT context")
+    }
 }
@@ -0,0 +1,180 @@
+use crate::prelude::*;
+
+/// Integer ciphertext [p * q + e + round(p / t) * m]_x0 of a single message m in Z_t
+///
+/// The AGCD form of [Per21], what remains of a scalar ciphertext after the sample
+/// extraction of one of its coefficients
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegerCiphertext {
+    pub value: BigInt,    // Ciphertext reduced mod x0
+    pub modulus: BigInt,  // Public modulus x0
+    pub fingerprint: u64, // Fingerprint of the key and parameters
+}
+
+/// Secret key of the integer ciphertexts extracted from the scalar ciphertexts of a key
+///
+/// The polynomial k cancels in the extraction, so only p is left
+pub struct ExtractedKey {
+    pub p: BigInt,        // Secret prime
+    pub x0: BigInt,       // Public modulus x0
+    pub fingerprint: u64, // Fingerprint of the scalar ciphertexts it comes from
+}
+
+/// Public key material to extract the coefficients of scalar ciphertexts
+///
+/// The constant coefficient of c * k_inv is sum c_j * sigma_j with sigma_0 = k_inv_0 and
+/// sigma_j = - k_inv_(n - j), because X^j * X^(n - j) = - 1. components j i is an integer
+/// ciphertext D_{gamma, rho}(p) + b^i * sigma_j, so the inner product with the digits of
+/// c_j adds a multiple of p and a noise of l * n digits of at most b / 2 against 2^rho
+#[derive(Debug, Clone)]
+pub struct ExtractionKey {
+    pub components: Vec<Vec<BigInt>>,
+    pub x0: BigInt,       // Public modulus of the ciphertexts
+    pub n: usize,         // Ring degree of the ciphertexts
+    pub fingerprint: u64, // Fingerprint of the key whose ciphertexts can be extracted
+}
+
+impl IntegerCiphertext {
+    /// Wraps an integer ciphertext, reducing it mod the modulus
+    pub fn new(value: BigInt, modulus: &BigInt, fingerprint: u64) -> Self {
+        Self {
+            value: value.rem_euclid(modulus),
+            modulus: modulus.clone(),
+            fingerprint,
+        }
+    }
+}
+
+impl GaheSecretKey {
+    /// Key of the integer ciphertexts extracted from the ciphertexts of this key
+    pub fn extracted(&self, context: &GaheContext) -> ExtractedKey {
+        ExtractedKey {
+            p: self.p.clone(),
+            x0: self.x0.clone(),
+            fingerprint: self.fingerprint(context),
+        }
+    }
+}
+
+impl ExtractionKey {
+    /// Generates the extraction key of a secret key, drawing the noise from rng
+    pub fn generate<R: Rng + ?Sized>(
+        sk: &GaheSecretKey,
+        context: &GaheContext,
+        params: &FhezParameters,
+        rng: &mut R,
+    ) -> Self {
+        let n = context.n;
+        let b_big = BigInt::from(params.b);
+        let powers_of_b: Vec<BigInt> = (0..params.l).map(|i| b_big.pow(i as u32)).collect();
+
+        let components = (0..n)
+            .map(|j| {
+                let sigma = if j == 0 {
+                    sk.k_inv.coefficients[0].clone()
+                } else {
+                    -&sk.k_inv.coefficients[n - j]
+                };
+                powers_of_b
+                    .iter()
+                    .map(|power| {
+                        (sample_d_with_rng(context.gamma, context.rho, &sk.p, rng) + power * &sigma)
+                            .rem_euclid(&sk.x0)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            components,
+            x0: sk.x0.clone(),
+            n,
+            fingerprint: sk.fingerprint(context),
+        }
+    }
+
+    /// Integer ciphertext of the i th coefficient of the message of a scalar ciphertext
+    ///
+    /// Multiplying by X^(- i) moves the coefficient i to the constant one, and the
+    /// coefficients below i wrap around negated, which only changes the ones not extracted.
+    /// The noise is the noise of that coefficient plus the extraction noise. Fails with
+    /// IndexOutOfRange if i is not below n
+    pub fn extract(
+        &self,
+        ciphertext: &ScalarCiphertext,
+        i: usize,
+        params: &FhezParameters,
+    ) -> Result<IntegerCiphertext, GaheError> {
+        if ciphertext.n != self.n {
+            return Err(GaheError::DegreeMismatch {
+                left: ciphertext.n,
+                right: self.n,
+            });
+        }
+        if ciphertext.modulus != self.x0 {
+            return Err(GaheError::ModulusMismatch);
+        }
+        if ciphertext.fingerprint != self.fingerprint {
+            return Err(GaheError::KeyMismatch {
+                left: ciphertext.fingerprint,
+                right: self.fingerprint,
+            });
+        }
+        if i >= self.n {
+            return Err(GaheError::IndexOutOfRange {
+                index: i,
+                n: self.n,
+            });
+        }
+
+        let rotated = ciphertext.poly.mul_by_monomial(-(i as i64));
+        let mut res = BigInt::zero();
+        for (coef, key_j) in rotated.coefficients.iter().zip(self.components.iter()) {
+            let digits =
+                signed_base_b_decomposition(&coef.rem_euclid(&self.x0), params.b, params.l);
+            for (digit, key_j_i) in digits.iter().zip(key_j.iter()) {
+                res += digit * key_j_i;
+            }
+        }
+        Ok(IntegerCiphertext::new(res, &self.x0, self.fingerprint))
+    }
+}
+
+/// Decrypts an integer ciphertext, returning the message in [0, t) and the centered noise
+///
+/// Fails with KeyMismatch if the ciphertext was not extracted from a ciphertext of this key
+pub fn decrypt_integer_with_noise(
+    key: &ExtractedKey,
+    context: &GaheContext,
+    ciphertext: &IntegerCiphertext,
+) -> Result<(BigInt, BigInt), GaheError> {
+    if ciphertext.fingerprint != key.fingerprint {
+        return Err(GaheError::KeyMismatch {
+            left: ciphertext.fingerprint,
+            right: key.fingerprint,
+        });
+    }
+    let alpha = round_bigint_division(&key.p, &context.t);
+    let phase = ciphertext.value.rem_euclid(&key.p);
+    let message = round_bigint_division(&(&phase * &context.t), &key.p).rem_euclid(&context.t);
+
+    // The noise is the phase minus round(p / t) * m, centered mod p
+    let noise = (&phase - &alpha * &message).rem_euclid(&key.p);
+    let noise = if &noise * 2 > key.p {
+        noise - &key.p
+    } else {
+        noise
+    };
+    Ok((message, noise))
+}
+
+/// Decrypts an integer ciphertext to its message in [0, t)
+///
+/// Fails with KeyMismatch if the ciphertext was not extracted from a ciphertext of this key
+pub fn decrypt_integer(
+    key: &ExtractedKey,
+    context: &GaheContext,
+    ciphertext: &IntegerCiphertext,
+) -> Result<BigInt, GaheError> {
+    Ok(decrypt_integer_with_noise(key, context, ciphertext)?.0)
+}
@@ -0,0 +1,440 @@
+use crate::prelude::*;
+use std::cmp::Ordering;
+
+/// Limbs of the coefficients of the GAHE products and of the DCRT reconstruction, 512 bits
+pub const COEFFICIENT_LIMBS: usize = 8;
+
+/// Signed integer of L limbs of 64 bits in two's complement, least significant limb first
+///
+/// The limbs are stored inline, so unlike a BigInt it never allocates. The arithmetic wraps
+/// around 2^(64 L) like the primitive integers, so L must leave room for the largest
+/// intermediate value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedInt<const L: usize> {
+    pub limbs: [u64; L],
+}
+
+impl<const L: usize> FixedInt<L> {
+    pub const ZERO: Self = Self { limbs: [0; L] };
+    pub const BITS: u32 = 64 * L as u32;
+
+    pub fn from_i64(value: i64) -> Self {
+        let mut limbs = [if value < 0 { u64::MAX } else { 0 }; L];
+        limbs[0] = value as u64;
+        Self { limbs }
+    }
+
+    pub fn from_u64(value: u64) -> Self {
+        let mut limbs = [0; L];
+        limbs[0] = value;
+        Self { limbs }
+    }
+
+    /// Converts a BigInt, or None if it does not fit in L limbs with its sign
+    pub fn from_bigint(value: &BigInt) -> Option<Self> {
+        let (sign, digits) = value.to_u64_digits();
+        if digits.len() > L {
+            return None;
+        }
+        let mut limbs = [0; L];
+        limbs[..digits.len()].copy_from_slice(&digits);
+        let magnitude = Self { limbs };
+        if magnitude.is_negative() {
+            return None;
+        }
+        Some(if sign == Sign::Minus {
+            -magnitude
+        } else {
+            magnitude
+        })
+    }
+
+    pub fn to_bigint(&self) -> BigInt {
+        if self.is_negative() {
+            return -(-*self).to_bigint();
+        }
+        self.limbs
+            .iter()
+            .rev()
+            .fold(BigInt::zero(), |res, &limb| (res << 64) + limb)
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.limbs[L - 1] >> 63 == 1
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.iter().all(|&limb| limb == 0)
+    }
+
+    /// Number of bits up to the most significant one, for a non negative value
+    pub fn bits(&self) -> u32 {
+        match self.limbs.iter().rposition(|&limb| limb != 0) {
+            Some(k) => 64 * k as u32 + 64 - self.limbs[k].leading_zeros(),
+            None => 0,
+        }
+    }
+
+    /// self * y for an unsigned y, cheaper than the product by a FixedInt
+    pub fn mul_u64(&self, y: u64) -> Self {
+        let mut res = [0; L];
+        let mut carry = 0u128;
+        for (res_k, &limb) in res.iter_mut().zip(self.limbs.iter()) {
+            let product = limb as u128 * y as u128 + carry;
+            *res_k = product as u64;
+            carry = product >> 64;
+        }
+        Self { limbs: res }
+    }
+
+    /// Remainder in [0, m) of the division by a positive m, like BigInt::rem_euclid
+    pub fn rem_euclid(&self, m: &Self) -> Self {
+        assert!(
+            !m.is_negative() && !m.is_zero(),
+            "The modulus must be positive"
+        );
+        if !self.is_negative() {
+            return self.unsigned_rem(m);
+        }
+        let r = (-*self).unsigned_rem(m);
+        if r.is_zero() {
+            r
+        } else {
+            *m - r
+        }
+    }
+
+    /// Remainder in [0, m) of the division by a positive m of one limb, a limb at a time
+    pub fn rem_u64(&self, m: u64) -> u64 {
+        assert!(m != 0, "The modulus must be positive");
+        let x = if self.is_negative() { -*self } else { *self };
+        let r = x
+            .limbs
+            .iter()
+            .rev()
+            .fold(0u128, |r, &limb| ((r << 64) | limb as u128) % m as u128) as u64;
+        if self.is_negative() && r != 0 {
+            m - r
+        } else {
+            r
+        }
+    }
+
+    /// Remainder of a non negative value by shift and subtract, from the highest multiple
+    /// m * 2^shift below it
+    fn unsigned_rem(&self, m: &Self) -> Self {
+        let mut r = *self;
+        let (r_bits, m_bits) = (r.bits(), m.bits());
+        if r_bits < m_bits {
+            return r;
+        }
+        for shift in (0..=r_bits - m_bits).rev() {
+            let shifted = *m << shift;
+            if r.cmp_unsigned(&shifted) != Ordering::Less {
+                r -= shifted;
+            }
+        }
+        r
+    }
+
+    fn cmp_unsigned(&self, other: &Self) -> Ordering {
+        self.limbs.iter().rev().cmp(other.limbs.iter().rev())
+    }
+
+    /// Signed digits in base b = 2^k, the same as signed_base_b_decomposition
+    ///
+    /// The low k bits are the remainder mod b also for negative values, so each digit is
+    /// taken with a mask and removed with a shift
+    pub fn signed_digits(&self, b: u64, digits: &mut [i64]) {
+        assert!(
+            b.is_power_of_two() && b < 1 << 63,
+            "The base must be a power of two below 2^63"
+        );
+        let shift = b.trailing_zeros();
+        let mut x = *self;
+        for digit in digits.iter_mut() {
+            let remainder = x.limbs[0] & (b - 1);
+            *digit = if 2 * remainder > b {
+                remainder as i64 - b as i64
+            } else {
+                remainder as i64
+            };
+            x = (x - Self::from_i64(*digit)) >> shift;
+        }
+    }
+}
+
+impl<const L: usize> Default for FixedInt<L> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<const L: usize> Ord for FixedInt<L> {
+    /// Signed order: two's complement values of the same sign compare like unsigned ones
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => self.cmp_unsigned(other),
+        }
+    }
+}
+
+impl<const L: usize> PartialOrd for FixedInt<L> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Adição
+impl<const L: usize> Add for FixedInt<L> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut res = [0; L];
+        let mut carry = false;
+        for (k, res_k) in res.iter_mut().enumerate() {
+            let (sum, c1) = self.limbs[k].overflowing_add(rhs.limbs[k]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *res_k = sum;
+            carry = c1 || c2;
+        }
+        Self { limbs: res }
+    }
+}
+
+impl<const L: usize> AddAssign for FixedInt<L> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+// Subtração
+impl<const L: usize> Sub for FixedInt<L> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mut res = [0; L];
+        let mut borrow = false;
+        for (k, res_k) in res.iter_mut().enumerate() {
+            let (diff, b1) = self.limbs[k].overflowing_sub(rhs.limbs[k]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *res_k = diff;
+            borrow = b1 || b2;
+        }
+        Self { limbs: res }
+    }
+}
+
+impl<const L: usize> SubAssign for FixedInt<L> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+// Negação
+impl<const L: usize> Neg for FixedInt<L> {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self::ZERO - self
+    }
+}
+
+// Multiplicação
+impl<const L: usize> Mul for FixedInt<L> {
+    type Output = Self;
+
+    /// Schoolbook product truncated to L limbs, which is the signed product mod 2^(64 L)
+    ///
+    /// The zero high limbs of non negative operands are skipped
+    fn mul(self, rhs: Self) -> Self::Output {
+        let rhs_len = L - rhs
+            .limbs
+            .iter()
+            .rev()
+            .take_while(|&&limb| limb == 0)
+            .count();
+        let mut res = [0u64; L];
+        for i in 0..L {
+            if self.limbs[i] == 0 {
+                continue;
+            }
+            let mut carry = 0u128;
+            let mut k = i;
+            for &rhs_j in rhs.limbs.iter().take(rhs_len.min(L - i)) {
+                let t = self.limbs[i] as u128 * rhs_j as u128 + res[k] as u128 + carry;
+                res[k] = t as u64;
+                carry = t >> 64;
+                k += 1;
+            }
+            while carry != 0 && k < L {
+                let t = res[k] as u128 + carry;
+                res[k] = t as u64;
+                carry = t >> 64;
+                k += 1;
+            }
+        }
+        Self { limbs: res }
+    }
+}
+
+// Deslocamentos
+impl<const L: usize> Shl<u32> for FixedInt<L> {
+    type Output = Self;
+    fn shl(self, shift: u32) -> Self::Output {
+        let (limb_shift, bit_shift) = ((shift / 64) as usize, shift % 64);
+        let mut res = [0; L];
+        for (k, res_k) in res.iter_mut().enumerate().skip(limb_shift) {
+            *res_k = self.limbs[k - limb_shift] << bit_shift;
+            if bit_shift > 0 && k > limb_shift {
+                *res_k |= self.limbs[k - limb_shift - 1] >> (64 - bit_shift);
+            }
+        }
+        Self { limbs: res }
+    }
+}
+
+impl<const L: usize> Shr<u32> for FixedInt<L> {
+    type Output = Self;
+
+    /// Arithmetic shift, rounding toward minus infinity like the primitive integers
+    fn shr(self, shift: u32) -> Self::Output {
+        let (limb_shift, bit_shift) = ((shift / 64) as usize, shift % 64);
+        let fill = if self.is_negative() { u64::MAX } else { 0 };
+        let limb = |k: usize| self.limbs.get(k).copied().unwrap_or(fill);
+        let mut res = [fill; L];
+        for (k, res_k) in res.iter_mut().enumerate() {
+            *res_k = limb(k + limb_shift) >> bit_shift;
+            if bit_shift > 0 {
+                *res_k |= limb(k + limb_shift + 1) << (64 - bit_shift);
+            }
+        }
+        Self { limbs: res }
+    }
+}
+
+/// Polynomial in Z[X] / <X^n + 1> with FixedInt coefficients, the fixed width counterpart of
+/// BigPolynomial
+///
+/// A negacyclic product of two polynomials with coefficients below 2^bits needs 2 bits +
+/// log2 n bits, see products_fit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedPolynomial<const L: usize> {
+    pub coefficients: Vec<FixedInt<L>>,
+}
+
+impl<const L: usize> FixedPolynomial<L> {
+    pub fn new(qt_coefficients: usize) -> Self {
+        Self {
+            coefficients: vec![FixedInt::ZERO; qt_coefficients],
+        }
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Whether the products of polynomials of degree n with coefficients below 2^bits fit
+    pub fn products_fit(bits: u64, n: usize) -> bool {
+        2 * bits + n.max(1).ilog2() as u64 + 2 <= FixedInt::<L>::BITS as u64
+    }
+
+    /// Converts a BigPolynomial, or None if a coefficient does not fit in L limbs
+    pub fn from_big(poly: &BigPolynomial) -> Option<Self> {
+        let coefficients = poly
+            .coefficients
+            .iter()
+            .map(FixedInt::from_bigint)
+            .collect::<Option<_>>()?;
+        Some(Self { coefficients })
+    }
+
+    pub fn to_big(&self) -> BigPolynomial {
+        BigPolynomial {
+            coefficients: self.coefficients.iter().map(FixedInt::to_bigint).collect(),
+        }
+    }
+
+    /// Reduces every coefficient to [0, m)
+    pub fn rem_euclid(&self, m: &FixedInt<L>) -> Self {
+        Self {
+            coefficients: self.coefficients.iter().map(|c| c.rem_euclid(m)).collect(),
+        }
+    }
+
+    /// Multiplies by the monomial X^k, see BigPolynomial::mul_by_monomial
+    pub fn mul_by_monomial(&self, k: i64) -> Self {
+        let n = self.degree();
+        let mut res = Self::new(n);
+        if n == 0 {
+            return res;
+        }
+
+        let shift = k.rem_euclid(2 * n as i64) as usize;
+        for (j, coef) in self.coefficients.iter().enumerate() {
+            let target = j + shift;
+            res.coefficients[target % n] = if (target / n).is_multiple_of(2) {
+                *coef
+            } else {
+                -*coef
+            };
+        }
+        res
+    }
+}
+
+// Adição
+impl<'b, const L: usize> Add<&'b FixedPolynomial<L>> for &FixedPolynomial<L> {
+    type Output = FixedPolynomial<L>;
+    fn add(self, rhs: &'b FixedPolynomial<L>) -> Self::Output {
+        let degree = self.degree().max(rhs.degree());
+        let coefficient = |poly: &FixedPolynomial<L>, i: usize| {
+            poly.coefficients.get(i).copied().unwrap_or_default()
+        };
+        FixedPolynomial {
+            coefficients: (0..degree)
+                .map(|i| coefficient(self, i) + coefficient(rhs, i))
+                .collect(),
+        }
+    }
+}
+
+// Subtração
+impl<'b, const L: usize> Sub<&'b FixedPolynomial<L>> for &FixedPolynomial<L> {
+    type Output = FixedPolynomial<L>;
+    fn sub(self, rhs: &'b FixedPolynomial<L>) -> Self::Output {
+        self + &-rhs
+    }
+}
+
+// Negação
+impl<const L: usize> Neg for &FixedPolynomial<L> {
+    type Output = FixedPolynomial<L>;
+    fn neg(self) -> Self::Output {
+        FixedPolynomial {
+            coefficients: self.coefficients.iter().map(|&c| -c).collect(),
+        }
+    }
+}
+
+// Multiplicação (Polinômio * Polinômio)
+impl<'b, const L: usize> Mul<&'b FixedPolynomial<L>> for &FixedPolynomial<L> {
+    type Output = FixedPolynomial<L>;
+    fn mul(self, rhs: &'b FixedPolynomial<L>) -> Self::Output {
+        let n = self.degree().max(rhs.degree());
+        let mut out = FixedPolynomial::new(n);
+        for (i, a_i) in self.coefficients.iter().enumerate() {
+            if a_i.is_zero() {
+                continue;
+            }
+            for (j, b_j) in rhs.coefficients.iter().enumerate() {
+                let term = *a_i * *b_j;
+                if i + j < n {
+                    out.coefficients[i + j] += term;
+                } else {
+                    out.coefficients[i + j - n] -= term;
+                }
+            }
+        }
+        out
+    }
+}
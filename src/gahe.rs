@@ -1,10 +1,12 @@
 use crate::prelude::*;
 
+#[derive(Debug, Clone)]
 pub struct GaheContext {
     pub t: BigInt,  // Message space module
     pub n: usize,   // Polynomial degree or ring dimension
     pub gamma: u64, // Bit length of q
     pub rho: u64,   // Bit length of r noise
+    pub eta: u64,   // Bit length of the secret prime p
 }
 
 pub struct GaheSecretKey {
@@ -12,14 +14,369 @@ pub struct GaheSecretKey {
     pub k: BigPolynomial,     // Secret polynomial
     pub k_inv: BigPolynomial, // Inverse of k mod x0
     pub x0: BigInt,           // Private module
+    pub key_id: u64,          // Random identifier drawn at key generation
+}
+
+/// Errors of the homomorphic operations between ciphertexts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GaheError {
+    /// The ciphertexts live in rings of different degrees
+    DegreeMismatch { left: usize, right: usize },
+
+    /// The ciphertexts are reduced modulo different public moduli
+    ModulusMismatch,
+
+    /// The ciphertexts were encrypted under different keys or parameters
+    KeyMismatch { left: u64, right: u64 },
+
+    /// The vector ciphertexts have different numbers of components
+    LengthMismatch { left: usize, right: usize },
+
+    /// The operation is not implemented for this message modulus
+    UnsupportedPlaintextModulus { t: u64 },
+
+    /// The key was transformed with another DcrtContext than the one given
+    ContextMismatch,
+
+    /// The coefficient index is not below the ring degree
+    IndexOutOfRange { index: usize, n: usize },
+}
+
+impl std::fmt::Display for GaheError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GaheError::DegreeMismatch { left, right } => {
+                write!(f, "ring degree mismatch: {left} and {right}")
+            }
+            GaheError::ModulusMismatch => write!(f, "ciphertext modulus mismatch"),
+            GaheError::LengthMismatch { left, right } => {
+                write!(f, "vector length mismatch: {left} and {right}")
+            }
+            GaheError::KeyMismatch { left, right } => {
+                write!(
+                    f,
+                    "key fingerprint mismatch: {left:#018x} and {right:#018x}"
+                )
+            }
+            GaheError::UnsupportedPlaintextModulus { t } => {
+                write!(f, "unsupported message modulus: t = {t}")
+            }
+            GaheError::ContextMismatch => write!(f, "DCRT context mismatch"),
+            GaheError::IndexOutOfRange { index, n } => {
+                write!(f, "coefficient index {index} out of range for degree {n}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GaheError {}
+
+/// Scalar ciphertext [(p * q + r + round(p / t) * m) * k]_x0 with the data needed to
+/// check that two ciphertexts can be combined
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScalarCiphertext {
+    pub poly: BigPolynomial, // Ciphertext polynomial reduced mod x0
+    pub modulus: BigInt,     // Public modulus x0
+    pub n: usize,            // Ring degree
+    pub fingerprint: u64,    // Fingerprint of the key and parameters
+}
+
+/// Scalar ciphertext switched to a modulus q, see ScalarCiphertext::mod_switch
+///
+/// It carries no fingerprint since it cannot be decrypted or combined with ciphertexts mod x0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwitchedCiphertext {
+    pub poly: BigPolynomial, // Coefficients rounded to [0, q)
+    pub modulus: BigInt,     // Module q
+    pub n: usize,            // Ring degree
+}
+
+/// Vector ciphertext of a message m, with l components [x_i + b^i * m]_x0 where each x_i
+/// is a scalar encryption of zero
+///
+/// The message is not multiplied by k: the inner product with the gadget decomposition of a
+/// scalar ciphertext c = e * k of m' is then e' * k + m * c, a scalar ciphertext of m * m'
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorCiphertext {
+    pub components: Vec<BigPolynomial>, // Components reduced mod x0
+    pub modulus: BigInt,                // Public modulus x0
+    pub n: usize,                       // Ring degree
+    pub fingerprint: u64,               // Fingerprint of the key and parameters
+}
+
+impl GaheContext {
+    /// Creates the GAHE context from the FHEZ parameters
+    ///
+    /// Fails if the parameters do not pass FhezParameters::validate_scheme
+    pub fn new(params: &FhezParameters) -> Result<Self, ParamError> {
+        params.validate_scheme()?;
+        Ok(Self {
+            t: params.t_bigint(),
+            n: params.n,
+            gamma: params.gamma.ceil() as u64,
+            rho: params.rho,
+            eta: params.eta,
+        })
+    }
+}
+
+impl GaheSecretKey {
+    /// Samples a fresh secret key as described in [Per21]
+    ///
+    /// p is a random prime of eta bits and x0 := p * q0 for a random prime q0 of gamma - eta bits,
+    /// so x0 has about gamma bits and every reduction mod x0 preserves the residue mod p
+    /// k is sampled uniformly from Z_x0[X] / <X^n + 1> until it is invertible
+    pub fn generate<R: Rng + ?Sized>(context: &GaheContext, rng: &mut R) -> Self {
+        assert!(
+            context.gamma > context.eta,
+            "gamma must be larger than the bit length of p"
+        );
+
+        let p = sample_prime(context.eta, rng);
+        let q0 = sample_prime(context.gamma - context.eta, rng);
+        let x0 = &p * &q0;
+        Self::with_modulus(p, x0, context, rng)
+    }
+
+    /// Samples a key with the same p and x0 and a fresh polynomial k
+    ///
+    /// Ciphertexts of both keys can be moved between them with a KeySwitchingKey
+    pub fn generate_sharing_modulus<R: Rng + ?Sized>(
+        &self,
+        context: &GaheContext,
+        rng: &mut R,
+    ) -> Self {
+        Self::with_modulus(self.p.clone(), self.x0.clone(), context, rng)
+    }
+
+    /// Samples k uniformly from Z_x0[X] / <X^n + 1> until it is invertible
+    fn with_modulus<R: Rng + ?Sized>(
+        p: BigInt,
+        x0: BigInt,
+        context: &GaheContext,
+        rng: &mut R,
+    ) -> Self {
+        loop {
+            let coefficients = (0..context.n)
+                .map(|_| rng.gen_bigint_range(&BigInt::zero(), &x0))
+                .collect();
+            let k = BigPolynomial { coefficients };
+
+            if let Some(k_inv) = k.inverse_mod(&x0) {
+                let key_id = rng.gen();
+                return Self {
+                    p,
+                    k,
+                    k_inv,
+                    x0,
+                    key_id,
+                };
+            }
+        }
+    }
+
+    /// Fingerprint identifying the ciphertexts of this key under the given context
+    ///
+    /// Hashes the random key_id drawn at key generation with the modulus x0, the ring degree
+    /// and the message space, so two keys sharing x0 get different fingerprints. Nothing
+    /// derived from k goes into the hash, since the fingerprint is public in every ciphertext.
+    /// The FNV-1a hash runs over explicit little endian bytes, so the value is the same on
+    /// every platform and Rust release
+    pub fn fingerprint(&self, context: &GaheContext) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write(&self.x0.to_signed_bytes_le());
+        hasher.write(&(context.n as u64).to_le_bytes());
+        hasher.write(&context.t.to_signed_bytes_le());
+        hasher.write(&self.key_id.to_le_bytes());
+        hasher.0
+    }
+}
+
+/// 64 bit FNV-1a hash of a sequence of byte strings
+///
+/// Each string is preceded by its length, so different sequences with the same
+/// concatenation do not collide
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let length = (bytes.len() as u64).to_le_bytes();
+        for byte in length.iter().chain(bytes.iter()) {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Compares the public data of two ciphertexts
+fn check_compatible(
+    left: (usize, &BigInt, u64),
+    right: (usize, &BigInt, u64),
+) -> Result<(), GaheError> {
+    if left.0 != right.0 {
+        return Err(GaheError::DegreeMismatch {
+            left: left.0,
+            right: right.0,
+        });
+    }
+    if left.1 != right.1 {
+        return Err(GaheError::ModulusMismatch);
+    }
+    if left.2 != right.2 {
+        return Err(GaheError::KeyMismatch {
+            left: left.2,
+            right: right.2,
+        });
+    }
+    Ok(())
+}
+
+impl ScalarCiphertext {
+    /// Wraps a ciphertext polynomial, reducing it mod the modulus
+    pub fn new(poly: BigPolynomial, modulus: &BigInt, fingerprint: u64) -> Self {
+        Self {
+            n: poly.degree(),
+            poly: poly % modulus,
+            modulus: modulus.clone(),
+            fingerprint,
+        }
+    }
+
+    /// Checks that both ciphertexts share the ring, the modulus and the key
+    pub fn check_compatible(&self, other: &ScalarCiphertext) -> Result<(), GaheError> {
+        check_compatible(
+            (self.n, &self.modulus, self.fingerprint),
+            (other.n, &other.modulus, other.fingerprint),
+        )
+    }
+
+    /// Homomorphic addition, an encryption of m1 + m2 mod t
+    pub fn try_add(&self, other: &ScalarCiphertext) -> Result<ScalarCiphertext, GaheError> {
+        self.check_compatible(other)?;
+        Ok(ScalarCiphertext::new(
+            &self.poly + &other.poly,
+            &self.modulus,
+            self.fingerprint,
+        ))
+    }
+
+    /// Homomorphic subtraction, an encryption of m1 - m2 mod t
+    pub fn try_sub(&self, other: &ScalarCiphertext) -> Result<ScalarCiphertext, GaheError> {
+        self.check_compatible(other)?;
+        Ok(ScalarCiphertext::new(
+            &self.poly - &other.poly,
+            &self.modulus,
+            self.fingerprint,
+        ))
+    }
+
+    /// Multiplication by a plaintext integer a, an encryption of a * m mod t
+    ///
+    /// The noise is multiplied by |a|
+    pub fn mul_scalar(&self, a: &BigInt) -> ScalarCiphertext {
+        ScalarCiphertext::new(a * &self.poly, &self.modulus, self.fingerprint)
+    }
+
+    /// Multiplication by a plaintext polynomial a, an encryption of a * m mod t
+    ///
+    /// The noise is multiplied by at most the sum of the absolute values of the coefficients of a
+    pub fn mul_plain(&self, a: &BigPolynomial) -> Result<ScalarCiphertext, GaheError> {
+        if a.degree() != self.n {
+            return Err(GaheError::DegreeMismatch {
+                left: self.n,
+                right: a.degree(),
+            });
+        }
+        Ok(ScalarCiphertext::new(
+            a * &self.poly,
+            &self.modulus,
+            self.fingerprint,
+        ))
+    }
+
+    /// Switches every coefficient from the modulus x0 to q, rounding q * c_j / x0
+    ///
+    /// The key k has no meaning mod q, so the result is a SwitchedCiphertext, which no
+    /// decryption accepts. What survives is c_j / x0 mod 1 up to 1 / 2q, which the
+    /// bootstrapping reads as exponents of X with q = 2n
+    pub fn mod_switch(&self, q: &BigInt) -> SwitchedCiphertext {
+        let coefficients = self
+            .poly
+            .coefficients
+            .iter()
+            .map(|c| mod_switch(c, &self.modulus, q))
+            .collect();
+        SwitchedCiphertext {
+            poly: BigPolynomial { coefficients },
+            modulus: q.clone(),
+            n: self.n,
+        }
+    }
+}
+
+impl Neg for &ScalarCiphertext {
+    type Output = ScalarCiphertext;
+
+    /// Homomorphic negation, an encryption of - m mod t
+    fn neg(self) -> Self::Output {
+        let zero = BigPolynomial::new(self.n);
+        ScalarCiphertext::new(&zero - &self.poly, &self.modulus, self.fingerprint)
+    }
+}
+
+impl Neg for ScalarCiphertext {
+    type Output = ScalarCiphertext;
+
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
+impl VectorCiphertext {
+    /// Checks that both ciphertexts share the ring, the modulus and the key
+    pub fn check_compatible(&self, other: &VectorCiphertext) -> Result<(), GaheError> {
+        check_compatible(
+            (self.n, &self.modulus, self.fingerprint),
+            (other.n, &other.modulus, other.fingerprint),
+        )
+    }
+
+    /// Homomorphic addition component by component, an encryption of m1 + m2
+    pub fn try_add(&self, other: &VectorCiphertext) -> Result<VectorCiphertext, GaheError> {
+        self.check_compatible(other)?;
+        if self.components.len() != other.components.len() {
+            return Err(GaheError::LengthMismatch {
+                left: self.components.len(),
+                right: other.components.len(),
+            });
+        }
+        let components = self
+            .components
+            .iter()
+            .zip(other.components.iter())
+            .map(|(a, b)| (a + b) % &self.modulus)
+            .collect();
+        Ok(VectorCiphertext {
+            components,
+            modulus: self.modulus.clone(),
+            n: self.n,
+            fingerprint: self.fingerprint,
+        })
+    }
 }
 
 /// Encrypts a message polynomial using the GAHE scheme for scalar ciphertext
-pub fn encrypt_scalar(
+///
+/// The products mod x0 run in the representation R, BigPolynomial being the reference
+pub fn encrypt_scalar<R: PolyRing>(
     sk: &GaheSecretKey,
     context: &GaheContext,
     message: &BigPolynomial,
-) -> BigPolynomial {
+    ring: &mut R::Context<'_>,
+) -> ScalarCiphertext {
     let q_bound = (BigInt::one() << context.gamma) / &sk.p;
     let r_bound = BigInt::one() << context.rho;
 
@@ -28,26 +385,278 @@ pub fn encrypt_scalar(
 
     // x := (p * q + r) * k mod x0
     let p_q_plus_r = (&sk.p * &q) + &r;
-    let x = (p_q_plus_r * &sk.k) % &sk.x0;
+    let x = R::mul_coefficients(&p_q_plus_r, &sk.k, &sk.x0, ring);
 
     // c := x + m * round(p / t) * k mod x0
     let alpha = round_bigint_division(&sk.p, &context.t);
-    let msg_term = (message * &(&alpha * &sk.k)) % &sk.x0;
+    let alpha_k = (&alpha * &sk.k) % &sk.x0;
+    let msg_term = R::mul_coefficients(message, &alpha_k, &sk.x0, ring);
 
-    (&x + &msg_term) % &sk.x0
+    ScalarCiphertext::new(&x + &msg_term, &sk.x0, sk.fingerprint(context))
+}
+
+/// Noise of a scalar ciphertext
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoiseMeasurement {
+    pub noise: BigPolynomial, // Centered error [c * k_inv]_p - round(p / t) * m mod p
+    pub bits: u64,            // Bit size of the infinity norm of the error
+    pub budget: i64,          // Bits left before the error reaches p / 2t
 }
 
 /// Decrypts a polynomial using the GAHE scheme
 ///
-/// c prime := c * k inverse mod x0
+/// c prime := c * k inverse mod x0, computed in the representation R
 /// output round(t * [c prime]_p / p) mod t
-pub fn decrypt_scalar(
+///
+/// Fails with KeyMismatch if the ciphertext was not encrypted under this key
+pub fn decrypt_scalar<R: PolyRing>(
+    sk: &GaheSecretKey,
+    context: &GaheContext,
+    ciphertext: &ScalarCiphertext,
+    ring: &mut R::Context<'_>,
+) -> Result<BigPolynomial, GaheError> {
+    let c_prime_mod_p = phase::<R>(sk, context, ciphertext, ring)?;
+    let scaled = round_poly_division(&(&context.t * &c_prime_mod_p), &sk.p);
+    Ok(scaled % &context.t)
+}
+
+/// Decrypts a polynomial and measures the noise left around the message
+///
+/// Fails with KeyMismatch if the ciphertext was not encrypted under this key
+pub fn decrypt_with_noise(
     sk: &GaheSecretKey,
     context: &GaheContext,
-    ciphertext: &BigPolynomial,
-) -> BigPolynomial {
-    let c_prime = (ciphertext * &sk.k_inv) % &sk.x0;
-    let c_prime_mod_p = centered_rem_poly(&c_prime, &sk.p);
+    ciphertext: &ScalarCiphertext,
+) -> Result<(BigPolynomial, NoiseMeasurement), GaheError> {
+    let c_prime_mod_p = phase::<BigPolynomial>(sk, context, ciphertext, &mut ())?;
     let scaled = round_poly_division(&(&context.t * &c_prime_mod_p), &sk.p);
-    scaled % &context.t
+    let message = scaled % &context.t;
+
+    // The error is what is left after removing the scaled message
+    let alpha = round_bigint_division(&sk.p, &context.t);
+    let noise = centered_rem_poly(&(&c_prime_mod_p - &(&alpha * &message)), &sk.p);
+
+    let bits = noise
+        .coefficients
+        .iter()
+        .map(|c| c.bits())
+        .max()
+        .unwrap_or(0);
+    let threshold = &sk.p / (BigInt::from(2u32) * &context.t);
+    let budget = threshold.bits() as i64 - bits as i64;
+
+    Ok((
+        message,
+        NoiseMeasurement {
+            noise,
+            bits,
+            budget,
+        },
+    ))
+}
+
+/// Measures the noise of a scalar ciphertext, see decrypt_with_noise
+pub fn noise_of(
+    sk: &GaheSecretKey,
+    context: &GaheContext,
+    ciphertext: &ScalarCiphertext,
+) -> Result<NoiseMeasurement, GaheError> {
+    Ok(decrypt_with_noise(sk, context, ciphertext)?.1)
+}
+
+/// The centered phase [c * k_inv mod x0]_p of a scalar ciphertext
+fn phase<R: PolyRing>(
+    sk: &GaheSecretKey,
+    context: &GaheContext,
+    ciphertext: &ScalarCiphertext,
+    ring: &mut R::Context<'_>,
+) -> Result<BigPolynomial, GaheError> {
+    let fingerprint = sk.fingerprint(context);
+    if ciphertext.fingerprint != fingerprint {
+        return Err(GaheError::KeyMismatch {
+            left: ciphertext.fingerprint,
+            right: fingerprint,
+        });
+    }
+    let c_prime = R::mul_coefficients(&ciphertext.poly, &sk.k_inv, &sk.x0, ring);
+    Ok(centered_rem_poly(&c_prime, &sk.p))
+}
+
+/// Encrypts a message polynomial as a vector ciphertext matching the gadget decomposition
+pub fn encrypt_vector(
+    sk: &GaheSecretKey,
+    context: &GaheContext,
+    params: &FhezParameters,
+    message: &BigPolynomial,
+) -> VectorCiphertext {
+    encrypt_vector_with_rng(sk, context, params, message, &mut rand::thread_rng())
+}
+
+/// encrypt_vector drawing the noise from the given generator
+pub fn encrypt_vector_with_rng<R: Rng + ?Sized>(
+    sk: &GaheSecretKey,
+    context: &GaheContext,
+    params: &FhezParameters,
+    message: &BigPolynomial,
+    rng: &mut R,
+) -> VectorCiphertext {
+    let b_big = BigInt::from(params.b);
+    let components = (0..params.l)
+        .map(|i| {
+            let zero = sample_r_with_rng(
+                context.gamma,
+                context.rho,
+                &sk.p,
+                &sk.k,
+                &sk.x0,
+                context.n,
+                rng,
+            );
+            (&zero + &(&b_big.pow(i as u32) * message)) % &sk.x0
+        })
+        .collect();
+    VectorCiphertext {
+        components,
+        modulus: sk.x0.clone(),
+        n: context.n,
+        fingerprint: sk.fingerprint(context),
+    }
+}
+
+/// Decrypts a vector ciphertext
+///
+/// [round(p / t) * k]_x0 is a noiseless scalar encryption of 1, so its product with the
+/// vector ciphertext is a scalar ciphertext of the message, decrypted with decrypt_scalar
+///
+/// Fails with KeyMismatch if the ciphertext was not encrypted under this key
+pub fn decrypt_vector(
+    sk: &GaheSecretKey,
+    context: &GaheContext,
+    params: &FhezParameters,
+    ciphertext: &VectorCiphertext,
+) -> Result<BigPolynomial, GaheError> {
+    let alpha = round_bigint_division(&sk.p, &context.t);
+    let one = ScalarCiphertext::new(&alpha * &sk.k, &sk.x0, ciphertext.fingerprint);
+    let scalar = external_product_poly(ciphertext, &one, params)
+        .expect("one shares the modulus and the key of the ciphertext");
+    decrypt_scalar::<BigPolynomial>(sk, context, &scalar, &mut ())
+}
+
+/// Product of a scalar ciphertext of m1 by a vector ciphertext of m2 over BigPolynomial
+///
+/// Computes [<g^-1(c), C>]_x0 exactly with external_product, giving a scalar ciphertext of
+/// m1 * m2 whose noise grows by at most l * n * b / 2 * 2^rho. It is the reference for the
+/// DCRT external_product
+pub fn external_product_poly(
+    vector: &VectorCiphertext,
+    scalar: &ScalarCiphertext,
+    params: &FhezParameters,
+) -> Result<ScalarCiphertext, GaheError> {
+    check_compatible(
+        (vector.n, &vector.modulus, vector.fingerprint),
+        (scalar.n, &scalar.modulus, scalar.fingerprint),
+    )?;
+    cross_key_product(
+        vector,
+        scalar,
+        scalar.fingerprint,
+        vector.fingerprint,
+        params,
+    )
+}
+
+/// External product of a scalar ciphertext under the key from by a vector ciphertext under
+/// the key to, a scalar ciphertext under to
+///
+/// Each fingerprint is checked against its ciphertext, and only the output carries to
+fn cross_key_product(
+    vector: &VectorCiphertext,
+    scalar: &ScalarCiphertext,
+    from: u64,
+    to: u64,
+    params: &FhezParameters,
+) -> Result<ScalarCiphertext, GaheError> {
+    for (fingerprint, expected) in [(scalar.fingerprint, from), (vector.fingerprint, to)] {
+        if fingerprint != expected {
+            return Err(GaheError::KeyMismatch {
+                left: fingerprint,
+                right: expected,
+            });
+        }
+    }
+    check_compatible(
+        (vector.n, &vector.modulus, to),
+        (scalar.n, &scalar.modulus, to),
+    )?;
+    if vector.components.len() != params.l {
+        return Err(GaheError::LengthMismatch {
+            left: vector.components.len(),
+            right: params.l,
+        });
+    }
+
+    let res = external_product(
+        &vector.components,
+        &mut scalar.poly.clone(),
+        params,
+        &mut (),
+    );
+    Ok(ScalarCiphertext::new(res, &scalar.modulus, to))
+}
+
+/// Key switching key from a secret key k to a key k' with the same p and x0
+///
+/// A vector ciphertext under k' of the polynomial k_inv * k' mod x0. The external product
+/// with a scalar ciphertext c = (p * q + e) * k mod x0 is then
+/// sum g^(-1)(c)_i * x_i + c * k_inv * k', that is (p * q' + e + R) * k' with R the gadget
+/// noise of the l * n digits, so the message and its noise carry over to k'.
+///
+/// The prime p cannot change: the multiple p * q of the phase is only known mod x0, and
+/// removing it takes the bootstrapping
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySwitchingKey {
+    pub ciphertext: VectorCiphertext, // Under the target key, whose fingerprint it carries
+    pub from: u64,                    // Fingerprint of the key the ciphertexts come from
+}
+
+impl KeySwitchingKey {
+    /// Generates the key that moves the ciphertexts of from to to, drawing the noise from rng
+    ///
+    /// Fails with ModulusMismatch if the keys do not share x0
+    pub fn generate<R: Rng + ?Sized>(
+        from: &GaheSecretKey,
+        to: &GaheSecretKey,
+        context: &GaheContext,
+        params: &FhezParameters,
+        rng: &mut R,
+    ) -> Result<Self, GaheError> {
+        if from.x0 != to.x0 {
+            return Err(GaheError::ModulusMismatch);
+        }
+        let message = (&from.k_inv * &to.k) % &to.x0;
+        Ok(Self {
+            ciphertext: encrypt_vector_with_rng(to, context, params, &message, rng),
+            from: from.fingerprint(context),
+        })
+    }
+}
+
+/// Moves a scalar ciphertext to the key of the key switching key
+///
+/// The output encrypts the same message with the noise of the input plus the gadget noise,
+/// and carries the fingerprint of the target key, so the source key no longer decrypts it.
+/// Fails with KeyMismatch if the input was not encrypted under the source key
+pub fn key_switch(
+    ciphertext: &ScalarCiphertext,
+    key: &KeySwitchingKey,
+    params: &FhezParameters,
+) -> Result<ScalarCiphertext, GaheError> {
+    // The key is a vector ciphertext under the target key, so is the product
+    cross_key_product(
+        &key.ciphertext,
+        ciphertext,
+        key.from,
+        key.ciphertext.fingerprint,
+        params,
+    )
 }
@@ -7,18 +7,28 @@
 #![allow(dead_code)]
 
 pub mod big_polynomial;
-// pub mod bootstrap;
+pub mod bootstrap;
 pub mod dcrt;
+pub mod extraction;
+pub mod fixed_int;
 pub mod gahe;
+pub mod noise_model;
+pub mod ntt;
 pub mod params;
 pub mod prelude;
+pub mod ring;
 pub mod util;
 
 pub use big_polynomial::*;
-// pub use bootstrap::*;
+pub use bootstrap::*;
 pub use dcrt::*;
+pub use extraction::*;
+pub use fixed_int::*;
 pub use gahe::*;
+pub use noise_model::*;
+pub use ntt::*;
 pub use params::*;
+pub use ring::*;
 pub use util::*;
 
 pub use concrete_fft::c64;
@@ -30,6 +40,6 @@ pub use num_traits::{Euclid, FromPrimitive, One, Signed, ToPrimitive, Zero};
 pub use rand::rngs::ThreadRng;
 pub use rand::Rng;
 pub use std::{
-    ops::{Add, AddAssign, Div, Mul, MulAssign, Rem, Sub},
+    ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Rem, Shl, Shr, Sub, SubAssign},
     time::{Duration, Instant},
 };
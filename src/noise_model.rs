@@ -0,0 +1,134 @@
+use crate::prelude::*;
+
+/// Worst case noise bounds of the GAHE operations, computed from the parameters only
+///
+/// Every bound is the infinity norm of the error e in [c * k_inv]_p = round(p / t) * m + e,
+/// the same quantity returned by noise_of, as an f64 since it can be larger than 2^64
+#[derive(Debug, Clone)]
+pub struct NoiseModel {
+    pub n: f64,
+    pub b: f64,
+    pub l: f64,
+    pub rho: u64,
+    pub gamma: u64,
+    pub eta: u64,
+    pub t: f64,
+}
+
+/// Summary of the noise model for one parameter set
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseEstimate {
+    pub fresh: f64,            // Noise of a fresh encryption
+    pub addition: f64,         // Noise of the sum of two fresh encryptions
+    pub external_product: f64, // Noise of a fresh scalar times a fresh vector ciphertext
+    pub bootstrap: f64,        // Noise of a bootstrapped ciphertext
+    pub decryption_bound: f64, // Lower bound of p / 2t, decryption fails above it
+    pub max_depth: usize,      // Number of chained external products that still decrypt
+    pub margin: f64,           // Bits between the fresh noise and the decryption bound
+}
+
+impl NoiseModel {
+    pub fn new(params: &FhezParameters) -> Self {
+        Self {
+            n: params.n_f64(),
+            b: params.b_f64(),
+            l: params.l_f64(),
+            rho: params.rho,
+            gamma: params.gamma.ceil() as u64,
+            eta: params.eta,
+            t: params.t as f64,
+        }
+    }
+
+    /// Bound of a fresh encryption: r is sampled in [- 2^rho, 2^rho]
+    pub fn fresh(&self) -> f64 {
+        2f64.powi(self.rho as i32)
+    }
+
+    /// Bound of the sum of two ciphertexts
+    ///
+    /// Reducing the sum of the messages mod t leaves round(p / t) * t - p, at most t / 2
+    pub fn add(&self, e1: f64, e2: f64) -> f64 {
+        e1 + e2 + self.t / 2.0
+    }
+
+    /// Bound of a ciphertext multiplied by the integer a
+    pub fn mul_scalar(&self, e: f64, a: f64) -> f64 {
+        a.abs() * (e + self.t / 2.0)
+    }
+
+    /// Noise added by the gadget decomposition: l * n digits of at most b / 2 against 2^rho
+    pub fn gadget_noise(&self) -> f64 {
+        self.l * self.n * self.b / 2.0 * self.fresh()
+    }
+
+    /// Bound of the external product of a scalar ciphertext of noise e by a vector ciphertext
+    ///
+    /// The message of the vector ciphertext has norm 1 at most n * (t - 1), and reducing
+    /// the product of the messages mod t adds t / 2 for each multiple of t in its coefficients
+    pub fn external_product(&self, e: f64) -> f64 {
+        let message_norm = self.n * (self.t - 1.0);
+        self.gadget_noise() + message_norm * e + message_norm * (self.t - 1.0) / 2.0
+    }
+
+    /// Bound of a ciphertext of noise e moved to another key
+    ///
+    /// The vector ciphertext of the key switching key encrypts k_inv * k', which cancels
+    /// against the ciphertext instead of multiplying the message, so only the gadget noise adds
+    pub fn key_switch(&self, e: f64) -> f64 {
+        e + self.gadget_noise()
+    }
+
+    /// Bound of a bootstrapped ciphertext
+    ///
+    /// The blind rotation takes one external product with a bit for each of the gamma - eta
+    /// bits of q0, and the bits do not amplify the noise, so each step only adds the gadget
+    /// noise. The two encodings of the test polynomials add the gadget noise twice more
+    pub fn bootstrap(&self) -> f64 {
+        let steps = (self.gamma - self.eta + 2) as f64;
+        steps * self.gadget_noise() + self.t
+    }
+
+    /// Rounding noise added by switching an integer ciphertext to exponents mod any q
+    ///
+    /// The phase c / p mod 1 is the sum of s_i * 2^i * c / x0 mod 1 over the bits s_i of q0,
+    /// which has at most gamma - eta + 1 bits, and each switched term rounds by at most 1 / 2.
+    /// The bound is in units of 1 / q, on top of the scaled noise q * e / p
+    pub fn mod_switch(&self) -> f64 {
+        (self.gamma - self.eta + 1) as f64 / 2.0
+    }
+
+    /// Lower bound of p / 2t, with p of eta bits at least 2^(eta - 1)
+    pub fn decryption_bound(&self) -> f64 {
+        2f64.powi(self.eta as i32 - 1) / (2.0 * self.t)
+    }
+
+    /// Bits left before the noise e reaches the decryption bound, negative when it fails
+    pub fn margin(&self, e: f64) -> f64 {
+        self.decryption_bound().log2() - e.log2()
+    }
+
+    /// Number of chained external products, starting from a fresh ciphertext, that decrypt
+    pub fn max_depth(&self) -> usize {
+        let mut depth = 0;
+        let mut noise = self.external_product(self.fresh());
+        while noise < self.decryption_bound() {
+            depth += 1;
+            noise = self.external_product(noise);
+        }
+        depth
+    }
+
+    pub fn estimate(&self) -> NoiseEstimate {
+        let fresh = self.fresh();
+        NoiseEstimate {
+            fresh,
+            addition: self.add(fresh, fresh),
+            external_product: self.external_product(fresh),
+            bootstrap: self.bootstrap(),
+            decryption_bound: self.decryption_bound(),
+            max_depth: self.max_depth(),
+            margin: self.margin(fresh),
+        }
+    }
+}
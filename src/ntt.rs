@@ -0,0 +1,155 @@
+use crate::prelude::*;
+
+/// Pre computed tables of the negacyclic NTT modulo one prime p congruent to 1 mod 2n
+///
+/// The transform of a is the NTT of a_j * psi^j for a primitive 2n th root of unity psi,
+/// so the pointwise product of two transforms is the transform of their product in
+/// Z_p[X] / <X^n + 1>. Every value is kept reduced in [0, p) and the primes are below 2^32,
+/// so the products fit in a u64 and the arithmetic is exact
+#[derive(Debug, Clone)]
+pub struct NttTable {
+    pub prime: u64,               // The prime p
+    pub n: usize,                 // The degree of the polynomials
+    pub psi_powers: Vec<u64>,     // psi^j for j in [0, n)
+    pub psi_inv_powers: Vec<u64>, // n^(-1) * psi^(-j), undoes the twist and the scaling
+    pub roots: Vec<u64>,          // omega^k for k in [0, n / 2) with omega = psi^2
+    pub inv_roots: Vec<u64>,      // omega^(-k) for k in [0, n / 2)
+}
+
+impl NttTable {
+    /// Builds the tables for a prime, or None if p is not congruent to 1 mod 2n
+    pub fn new(prime: u32, n: usize) -> Option<Self> {
+        let p = prime as u64;
+        let two_n = 2 * n as u64;
+        if n < 2 || !n.is_power_of_two() || !(p - 1).is_multiple_of(two_n) {
+            return None;
+        }
+
+        // x^((p - 1) / 2n) has an order dividing 2n, and it is exactly 2n when its n th power is - 1
+        let psi = (2..p)
+            .map(|x| pow_mod(x, (p - 1) / two_n, p))
+            .find(|&psi| pow_mod(psi, n as u64, p) == p - 1)?;
+        let psi_inv = pow_mod(psi, p - 2, p);
+        let n_inv = pow_mod(n as u64, p - 2, p);
+
+        let powers = |base: u64, first: u64, count: usize| -> Vec<u64> {
+            let mut res = Vec::with_capacity(count);
+            let mut current = first;
+            for _ in 0..count {
+                res.push(current);
+                current = mul_mod(current, base, p);
+            }
+            res
+        };
+
+        Some(Self {
+            prime: p,
+            n,
+            psi_powers: powers(psi, 1, n),
+            psi_inv_powers: powers(psi_inv, n_inv, n),
+            roots: powers(mul_mod(psi, psi, p), 1, n / 2),
+            inv_roots: powers(mul_mod(psi_inv, psi_inv, p), 1, n / 2),
+        })
+    }
+
+    /// Replaces the coefficients of a polynomial mod p by its negacyclic transform
+    pub fn forward(&self, a: &mut [u64]) {
+        for (coef, psi_j) in a.iter_mut().zip(self.psi_powers.iter()) {
+            *coef = mul_mod(*coef, *psi_j, self.prime);
+        }
+        cyclic_ntt(a, &self.roots, self.prime);
+    }
+
+    /// Replaces a negacyclic transform by the coefficients of its polynomial mod p
+    pub fn inverse(&self, a: &mut [u64]) {
+        cyclic_ntt(a, &self.inv_roots, self.prime);
+        for (coef, psi_inv_j) in a.iter_mut().zip(self.psi_inv_powers.iter()) {
+            *coef = mul_mod(*coef, *psi_inv_j, self.prime);
+        }
+    }
+
+    /// Multiplies a transform by the monomial X^k pointwise, for any k
+    ///
+    /// The m th value is the polynomial at X = psi^(2m + 1), so X^k is psi^((2m + 1) * k),
+    /// read from psi_powers with psi^(n + j) = - psi^j
+    pub fn mul_by_monomial(&self, a: &mut [u64], k: i64) {
+        let two_n = 2 * self.n as i64;
+        let k = k.rem_euclid(two_n);
+        for (m, value) in a.iter_mut().enumerate() {
+            let e = ((2 * m as i64 + 1) * k % two_n) as usize;
+            let root = if e < self.n {
+                self.psi_powers[e]
+            } else {
+                self.prime - self.psi_powers[e - self.n]
+            };
+            *value = mul_mod(*value, root, self.prime);
+        }
+    }
+}
+
+/// Iterative radix 2 Cooley Tukey NTT with the powers of an n th root of unity
+///
+/// The input is permuted in bit reversed order first, so the output is in natural order
+fn cyclic_ntt(a: &mut [u64], roots: &[u64], p: u64) {
+    let n = a.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let step = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..half {
+                let u = a[start + k];
+                let v = mul_mod(a[start + k + half], roots[k * step], p);
+                a[start + k] = add_mod(u, v, p);
+                a[start + k + half] = sub_mod(u, v, p);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// a + b mod p for a, b in [0, p)
+pub fn add_mod(a: u64, b: u64, p: u64) -> u64 {
+    let res = a + b;
+    if res >= p {
+        res - p
+    } else {
+        res
+    }
+}
+
+/// a - b mod p for a, b in [0, p)
+pub fn sub_mod(a: u64, b: u64, p: u64) -> u64 {
+    if a >= b {
+        a - b
+    } else {
+        a + p - b
+    }
+}
+
+/// a * b mod p for a, b in [0, p) with p below 2^32
+pub fn mul_mod(a: u64, b: u64, p: u64) -> u64 {
+    a * b % p
+}
+
+/// base^exp mod p by square and multiply
+pub fn pow_mod(base: u64, mut exp: u64, p: u64) -> u64 {
+    let mut res = 1 % p;
+    let mut base = base % p;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = mul_mod(res, base, p);
+        }
+        base = mul_mod(base, base, p);
+        exp >>= 1;
+    }
+    res
+}
@@ -12,6 +12,98 @@ pub static PRIMES_20: &[u32] = &[
     1048709, 1048717, 1048721, 1048759, 1048783, 1048793, 1048807, 1048819, 1048829, 1048849,
 ];
 
+/// Primes of 30 bits congruent to 1 mod 2^17, for the NTT of degrees up to 2^16
+pub static NTT_PRIMES_30: &[u32] = &[
+    1073479681, 1071513601, 1070727169, 1068236801, 1065484289, 1064697857, 1062862849, 1062469633,
+    1060765697, 1056440321, 1056178177, 1055260673, 1054212097, 1053818881, 1052508161, 1051721729,
+    1049100289, 1048707073, 1045430273, 1043464193,
+];
+
+/// Reasons why a set of FHEZ parameters cannot be used
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamError {
+    /// The ring degree must be a power of two for the FFT
+    InvalidDegree { n: usize },
+
+    /// The message space needs at least two values
+    InvalidMessageModulus { t: u64 },
+
+    /// b^l must exceed the 2^gamma values of a ciphertext coefficient, since the centered
+    /// signed digits need room for a final carry
+    GadgetTooShort { coverage_bits: f64, gamma: f64 },
+
+    /// x0 = p * q0 needs q0 with at least one bit
+    PrimeTooLarge { eta: u64, gamma: f64 },
+
+    /// 2^rho + (t - 1) / 2 must be smaller than p / 2t for decryption to work
+    NoiseTooLarge { rho: u64, eta: u64, t: u64 },
+
+    /// The product of the DCRT primes must hold the coefficients of an external product
+    InsufficientPrimes {
+        required_bits: u64,
+        available_bits: u64,
+    },
+
+    /// The NTT needs a primitive 2n th root of unity modulo each prime
+    NoNttRoot { n: usize, prime: u32 },
+
+    /// The FFT products must stay below the f64 mantissa to be rounded back exactly
+    FftPrecision {
+        required_bits: f64,
+        mantissa_bits: u32,
+    },
+}
+
+impl std::fmt::Display for ParamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamError::InvalidDegree { n } => {
+                write!(f, "ring degree {n} is not a power of two")
+            }
+            ParamError::InvalidMessageModulus { t } => {
+                write!(f, "message modulus {t} is smaller than 2")
+            }
+            ParamError::GadgetTooShort {
+                coverage_bits,
+                gamma,
+            } => write!(
+                f,
+                "gadget covers {coverage_bits:.1} bits, less than gamma + 1 = {}",
+                gamma + 1.0
+            ),
+            ParamError::PrimeTooLarge { eta, gamma } => {
+                write!(f, "eta = {eta} leaves no room for q0 in gamma = {gamma}")
+            }
+            ParamError::NoiseTooLarge { rho, eta, t } => write!(
+                f,
+                "noise of {rho} bits does not fit in p / 2t for eta = {eta} and t = {t}"
+            ),
+            ParamError::InsufficientPrimes {
+                required_bits,
+                available_bits,
+            } => write!(
+                f,
+                "DCRT needs a modulus of {required_bits} bits but the primes give {available_bits}"
+            ),
+            ParamError::NoNttRoot { n, prime } => {
+                write!(
+                    f,
+                    "prime {prime} has no primitive root of order 2n for n = {n}"
+                )
+            }
+            ParamError::FftPrecision {
+                required_bits,
+                mantissa_bits,
+            } => write!(
+                f,
+                "FFT products need {required_bits:.1} bits but f64 has {mantissa_bits}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
 /// Parameters of the FHEZ scheme
 #[derive(Debug, Clone)]
 pub struct FhezParameters {
@@ -30,6 +122,9 @@ pub struct FhezParameters {
     /// Bit length of the noise r in the encryption: r belongs to the interval negative 2^rho to 2^rho
     pub rho: u64,
 
+    /// Bit length of the secret prime p
+    pub eta: u64,
+
     /// Module of the message space
     pub t: u64,
 
@@ -45,6 +140,7 @@ impl FhezParameters {
             l: 10,
             gamma: 206.0,
             rho: 16,
+            eta: 96,
             t: 4,
             primes: PRIMES_20,
         }
@@ -57,11 +153,94 @@ impl FhezParameters {
             l: 8,
             gamma: 150.0,
             rho: 16,
+            eta: 64,
             t: 2,
             primes: PRIMES_20,
         }
     }
 
+    /// Checks that the parameters give a correct scheme with the FFT DCRT backend
+    ///
+    /// The DCRT must hold the coefficients of an external product, up to l * n * b * 2^gamma,
+    /// and its FFT multiplies residues below the largest prime, whose sums of l * n products
+    /// must fit in an f64
+    pub fn validate(&self) -> Result<(), ParamError> {
+        self.validate_scheme()?;
+        self.validate_primes(self.primes)?;
+
+        let largest_prime = self.primes.iter().copied().max().unwrap_or(0) as f64;
+        let fft_bits = 2.0 * largest_prime.log2() + self.n_f64().log2() + self.l_f64().log2();
+        if fft_bits >= f64::MANTISSA_DIGITS as f64 {
+            return Err(ParamError::FftPrecision {
+                required_bits: fft_bits,
+                mantissa_bits: f64::MANTISSA_DIGITS,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks the conditions of the GAHE scheme, independently of the DCRT backend
+    ///
+    /// p has eta bits, so p / 2t is at least 2^(eta - 1) / 2t
+    pub fn validate_scheme(&self) -> Result<(), ParamError> {
+        if self.n < 2 || !self.n.is_power_of_two() {
+            return Err(ParamError::InvalidDegree { n: self.n });
+        }
+        if self.t < 2 {
+            return Err(ParamError::InvalidMessageModulus { t: self.t });
+        }
+
+        let coverage_bits = self.l_f64() * self.b_f64().log2();
+        if self.b < 2 || coverage_bits < self.gamma + 1.0 {
+            return Err(ParamError::GadgetTooShort {
+                coverage_bits,
+                gamma: self.gamma,
+            });
+        }
+
+        if self.eta as f64 >= self.gamma {
+            return Err(ParamError::PrimeTooLarge {
+                eta: self.eta,
+                gamma: self.gamma,
+            });
+        }
+
+        let noise = 2f64.powi(self.rho as i32) + (self.t - 1) as f64 / 2.0;
+        let bound = 2f64.powi(self.eta as i32 - 1) / (2.0 * self.t as f64);
+        if self.eta == 0 || noise >= bound {
+            return Err(ParamError::NoiseTooLarge {
+                rho: self.rho,
+                eta: self.eta,
+                t: self.t,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the product of the primes is large enough for the DCRT
+    pub fn validate_primes(&self, primes: &[u32]) -> Result<(), ParamError> {
+        let required_bits = self.dcrt_modulus_bits();
+        let available_bits = primes.iter().fold(BigInt::one(), |m, &p| m * p).bits();
+        if available_bits < required_bits {
+            return Err(ParamError::InsufficientPrimes {
+                required_bits,
+                available_bits,
+            });
+        }
+        Ok(())
+    }
+
+    /// Bit size of the DCRT modulus needed by an external product
+    pub fn dcrt_modulus_bits(&self) -> u64 {
+        let size = self.gamma
+            + f64::ceil(f64::log2(self.l_f64()))
+            + f64::log2(self.b_f64())
+            + f64::log2(self.n_f64());
+        size.ceil() as u64
+    }
+
     pub fn b_f64(&self) -> f64 {
         self.b as f64
     }
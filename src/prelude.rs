@@ -1,8 +1,13 @@
 pub use crate::big_polynomial::*;
-// pub use crate::bootstrap::*;
+pub use crate::bootstrap::*;
 pub use crate::dcrt::*;
+pub use crate::extraction::*;
+pub use crate::fixed_int::*;
 pub use crate::gahe::*;
+pub use crate::noise_model::*;
+pub use crate::ntt::*;
 pub use crate::params::*;
+pub use crate::ring::*;
 pub use crate::util::*;
 
 pub use concrete_fft::c64;
@@ -14,6 +19,6 @@ pub use num_traits::{Euclid, FromPrimitive, One, Signed, ToPrimitive, Zero};
 pub use rand::rngs::ThreadRng;
 pub use rand::Rng;
 pub use std::{
-    ops::{Add, AddAssign, Div, Mul, MulAssign, Rem, Sub},
+    ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Rem, Shl, Shr, Sub, SubAssign},
     time::{Duration, Instant},
 };
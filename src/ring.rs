@@ -0,0 +1,228 @@
+use crate::prelude::*;
+
+/// Arithmetic in Z[X] / <X^n + 1> shared by the polynomial representations
+///
+/// Context is what a representation needs to enter and leave the coefficient form,
+/// nothing for BigPolynomial and a DcrtRing for Dcrt. encrypt_scalar, decrypt_scalar and
+/// external_product are generic over this trait, so the scheme runs on any backend
+pub trait PolyRing: Clone {
+    type Context<'a>;
+
+    /// Converts a polynomial in coefficient form to this representation
+    fn from_coefficients(a: &BigPolynomial, context: &mut Self::Context<'_>) -> Self;
+
+    /// Converts back to the coefficient form
+    fn to_coefficients(&self, context: &mut Self::Context<'_>) -> BigPolynomial;
+
+    fn add(&self, rhs: &Self) -> Self;
+
+    fn sub(&self, rhs: &Self) -> Self;
+
+    fn mul(&self, rhs: &Self) -> Self;
+
+    fn neg(&self) -> Self;
+
+    /// Multiplies every coefficient by the integer a
+    fn mul_scalar(&self, a: &BigInt, context: &mut Self::Context<'_>) -> Self;
+
+    /// Product of two polynomials in coefficient form reduced mod modulus
+    ///
+    /// The default multiplies in the representation, which must then hold coefficients
+    /// up to n * modulus^2
+    fn mul_coefficients(
+        a: &BigPolynomial,
+        b: &BigPolynomial,
+        modulus: &BigInt,
+        context: &mut Self::Context<'_>,
+    ) -> BigPolynomial {
+        let product = Self::from_coefficients(a, context).mul(&Self::from_coefficients(b, context));
+        product.to_coefficients(context) % modulus
+    }
+
+    /// The l digit polynomials g^(-1)(a) of the gadget decomposition in this representation
+    ///
+    /// The default goes through the coefficient form with gadget_decompose
+    fn gadget_decompose(
+        &mut self,
+        params: &FhezParameters,
+        context: &mut Self::Context<'_>,
+    ) -> Vec<Self> {
+        gadget_decompose(&self.to_coefficients(context), params)
+            .iter()
+            .map(|digits| Self::from_coefficients(digits, context))
+            .collect()
+    }
+
+    /// The sum of the products a_i * b_i
+    fn inner_product(a: &[Self], b: &[Self]) -> Self {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "Input vectors for the inner product must have the same length"
+        );
+        let mut products = a.iter().zip(b.iter()).map(|(a_i, b_i)| a_i.mul(b_i));
+        let first = products
+            .next()
+            .expect("Cannot compute inner product of empty vectors");
+        products.fold(first, |acc, product| acc.add(&product))
+    }
+}
+
+impl PolyRing for BigPolynomial {
+    type Context<'a> = ();
+
+    fn from_coefficients(a: &BigPolynomial, _: &mut ()) -> Self {
+        a.clone()
+    }
+
+    fn to_coefficients(&self, _: &mut ()) -> BigPolynomial {
+        self.clone()
+    }
+
+    fn add(&self, rhs: &Self) -> Self {
+        self + rhs
+    }
+
+    fn sub(&self, rhs: &Self) -> Self {
+        self - rhs
+    }
+
+    fn mul(&self, rhs: &Self) -> Self {
+        self * rhs
+    }
+
+    fn neg(&self) -> Self {
+        &BigPolynomial::new(self.degree()) - self
+    }
+
+    fn mul_scalar(&self, a: &BigInt, _: &mut ()) -> Self {
+        a * self
+    }
+}
+
+/// The keys and the ciphertexts stay BigPolynomial, so encrypt_scalar and decrypt_scalar on
+/// FixedPolynomial convert k, x0 and the ciphertext on each call. Only the products and the
+/// reduction mod x0 run in the limbs
+impl<const L: usize> PolyRing for FixedPolynomial<L> {
+    type Context<'a> = ();
+
+    /// Panics if a coefficient does not fit in L limbs
+    fn from_coefficients(a: &BigPolynomial, _: &mut ()) -> Self {
+        Self::from_big(a).expect("The coefficients fit in the limbs")
+    }
+
+    fn to_coefficients(&self, _: &mut ()) -> BigPolynomial {
+        self.to_big()
+    }
+
+    fn add(&self, rhs: &Self) -> Self {
+        self + rhs
+    }
+
+    fn sub(&self, rhs: &Self) -> Self {
+        self - rhs
+    }
+
+    fn mul(&self, rhs: &Self) -> Self {
+        self * rhs
+    }
+
+    fn neg(&self) -> Self {
+        -self
+    }
+
+    fn mul_scalar(&self, a: &BigInt, _: &mut ()) -> Self {
+        let a = FixedInt::from_bigint(a).expect("The scalar fits in the limbs");
+        Self {
+            coefficients: self.coefficients.iter().map(|&c| a * c).collect(),
+        }
+    }
+
+    /// Reduces a and b mod modulus first, so the product fits whenever products_fit holds
+    /// for the bits of the modulus, and reduces the product without leaving the limbs
+    fn mul_coefficients(
+        a: &BigPolynomial,
+        b: &BigPolynomial,
+        modulus: &BigInt,
+        ring: &mut (),
+    ) -> BigPolynomial {
+        let m = FixedInt::from_bigint(modulus).expect("The modulus fits in the limbs");
+        let a = Self::from_coefficients(&(a % modulus), ring);
+        let b = Self::from_coefficients(&(b % modulus), ring);
+        (&a * &b).rem_euclid(&m).to_big()
+    }
+}
+
+/// Everything the Dcrt representation needs besides the polynomials
+pub struct DcrtRing<'a> {
+    pub context: &'a DcrtContext,
+    pub params: &'a FhezParameters,
+}
+
+impl PolyRing for Dcrt {
+    type Context<'a> = DcrtRing<'a>;
+
+    fn from_coefficients(a: &BigPolynomial, ring: &mut DcrtRing<'_>) -> Self {
+        to_dcrt(a, ring.context, ring.params)
+    }
+
+    fn to_coefficients(&self, ring: &mut DcrtRing<'_>) -> BigPolynomial {
+        from_dcrt(&mut self.clone(), ring.context, ring.params)
+    }
+
+    fn add(&self, rhs: &Self) -> Self {
+        self + rhs
+    }
+
+    fn sub(&self, rhs: &Self) -> Self {
+        self - rhs
+    }
+
+    fn mul(&self, rhs: &Self) -> Self {
+        self * rhs
+    }
+
+    fn neg(&self) -> Self {
+        -self
+    }
+
+    /// Exact for any a, see Dcrt::mul_integer
+    fn mul_scalar(&self, a: &BigInt, ring: &mut DcrtRing<'_>) -> Self {
+        self.mul_integer(a, ring.context, ring.params)
+    }
+
+    /// The context is sized for external products, so a is decomposed with the gadget first:
+    /// the products of sum g^(-1)(a)_i * [B^i * b]_modulus have digits below B / 2 against
+    /// coefficients below the modulus, like an external product
+    fn mul_coefficients(
+        a: &BigPolynomial,
+        b: &BigPolynomial,
+        modulus: &BigInt,
+        ring: &mut DcrtRing<'_>,
+    ) -> BigPolynomial {
+        let base = BigInt::from(ring.params.b);
+        let mut power_times_b = b % modulus;
+        let mut res: Option<Dcrt> = None;
+        for digit in gadget_decompose(&(a % modulus), ring.params) {
+            let term = &Self::from_coefficients(&digit, ring)
+                * &Self::from_coefficients(&power_times_b, ring);
+            res = Some(match res {
+                Some(acc) => &acc + &term,
+                None => term,
+            });
+            power_times_b = (&base * &power_times_b) % modulus;
+        }
+        let res = res.expect("the gadget decomposition has at least one level");
+        res.to_coefficients(ring) % modulus
+    }
+
+    /// Decomposes without leaving the DCRT domain, see gadget_decompose_dcrt
+    fn gadget_decompose(&mut self, params: &FhezParameters, ring: &mut DcrtRing<'_>) -> Vec<Self> {
+        gadget_decompose_dcrt(self, ring.context, params)
+    }
+
+    /// Computes the products in parallel when the context is parallel, see inner_product
+    fn inner_product(a: &[Self], b: &[Self]) -> Self {
+        inner_product(a, b)
+    }
+}
@@ -1,30 +1,12 @@
 use crate::prelude::*;
 
-pub fn to_fft<'a>(poly: &'a mut [Complex<f64>], plan: &'a mut Plan) -> &'a mut [Complex<f64>] {
-    let mut scratch_memory = GlobalPodBuffer::new(plan.fft_scratch().unwrap());
-    let mut stack = PodStack::new(&mut scratch_memory);
-    plan.fwd(poly, stack.rb_mut());
-    poly
-}
-
-pub fn from_fft<'a>(
-    poly: &'a mut [Complex<f64>],
-    plan: &'a mut Plan,
-    params: &FhezParameters,
-) -> &'a mut [Complex<f64>] {
-    let mut scratch_memory = GlobalPodBuffer::new(plan.fft_scratch().unwrap());
-    let mut stack = PodStack::new(&mut scratch_memory);
-    plan.inv(poly, stack.rb_mut());
-    for coeff in poly.iter_mut() {
-        *coeff /= params.n as f64;
-    }
-    poly
-}
-
 // D_{γ,ρ}(p)
 pub fn sample_d(gamma: u64, rho: u64, p: &BigInt) -> BigInt {
-    let mut rng = rand::thread_rng();
+    sample_d_with_rng(gamma, rho, p, &mut rand::thread_rng())
+}
 
+/// sample_d drawing from the given generator
+pub fn sample_d_with_rng<R: Rng + ?Sized>(gamma: u64, rho: u64, p: &BigInt, rng: &mut R) -> BigInt {
     // Sample q from [0, ⌊2^γ/p⌋]
     let two_gamma = BigInt::one() << gamma;
     let q_bound = &two_gamma / p;
@@ -39,7 +21,20 @@ pub fn sample_d(gamma: u64, rho: u64, p: &BigInt) -> BigInt {
 
 // P_{N,γ,ρ}(p)
 pub fn sample_p(gamma: u64, rho: u64, p: &BigInt, n: usize) -> BigPolynomial {
-    let coefficients: Vec<BigInt> = (0..n).map(|_| sample_d(gamma, rho, p)).collect();
+    sample_p_with_rng(gamma, rho, p, n, &mut rand::thread_rng())
+}
+
+/// sample_p drawing from the given generator
+pub fn sample_p_with_rng<R: Rng + ?Sized>(
+    gamma: u64,
+    rho: u64,
+    p: &BigInt,
+    n: usize,
+    rng: &mut R,
+) -> BigPolynomial {
+    let coefficients: Vec<BigInt> = (0..n)
+        .map(|_| sample_d_with_rng(gamma, rho, p, rng))
+        .collect();
     BigPolynomial { coefficients }
 }
 
@@ -51,10 +46,78 @@ pub fn sample_r(
     x_0: &BigInt,
     n: usize,
 ) -> BigPolynomial {
-    let c = sample_p(gamma, rho, p, n);
+    sample_r_with_rng(gamma, rho, p, k, x_0, n, &mut rand::thread_rng())
+}
+
+/// sample_r drawing from the given generator
+pub fn sample_r_with_rng<R: Rng + ?Sized>(
+    gamma: u64,
+    rho: u64,
+    p: &BigInt,
+    k: &BigPolynomial,
+    x_0: &BigInt,
+    n: usize,
+    rng: &mut R,
+) -> BigPolynomial {
+    let c = sample_p_with_rng(gamma, rho, p, n, rng);
     (c * k) % x_0
 }
 
+/// Miller Rabin probabilistic primality test with the given number of random bases
+pub fn is_probable_prime<R: Rng + ?Sized>(n: &BigInt, rounds: usize, rng: &mut R) -> bool {
+    let two = BigInt::from(2u32);
+    if *n < two {
+        return false;
+    }
+
+    // Trial division by small primes also handles every n below 41
+    for small in [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        let small = BigInt::from(small);
+        if *n == small {
+            return true;
+        }
+        if (n % &small).is_zero() {
+            return false;
+        }
+    }
+
+    // Writes n - 1 = d * 2^s with d odd
+    let n_minus_1 = n - BigInt::one();
+    let s = n_minus_1.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_1 >> s;
+
+    'witness: for _ in 0..rounds {
+        let a = rng.gen_bigint_range(&two, &n_minus_1);
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == n_minus_1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = (&x * &x) % n;
+            if x == n_minus_1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Samples a random prime with exactly the given bit length
+pub fn sample_prime<R: Rng + ?Sized>(bits: u64, rng: &mut R) -> BigInt {
+    assert!(bits >= 2, "A prime needs at least 2 bits");
+    let top_bit = BigInt::one() << (bits - 1);
+
+    loop {
+        // Forces the top bit so the candidate has exactly bits bits and the low bit so it is odd
+        let candidate = rng.gen_bigint_range(&BigInt::zero(), &top_bit) + &top_bit;
+        let candidate = candidate | BigInt::one();
+        if is_probable_prime(&candidate, 40, rng) {
+            return candidate;
+        }
+    }
+}
+
 /// Symmetric reduction of a modulo n
 /// Returns the unique integer r such that r ≡ a (mod n) and r ∈ (-n/2, n/2]
 pub fn sym_mod(a: BigInt, n: i64) -> BigInt {
@@ -183,6 +246,13 @@ pub fn round_bigint_division(num: &BigInt, den: &BigInt) -> BigInt {
     }
 }
 
+/// Switches an integer from the modulus from to the modulus to, round(to * [a]_from / from) mod to
+///
+/// The result over to is within 1 / (2 * to) of a / from mod 1
+pub fn mod_switch(a: &BigInt, from: &BigInt, to: &BigInt) -> BigInt {
+    round_bigint_division(&(a.rem_euclid(from) * to), from).rem_euclid(to)
+}
+
 pub fn round_poly_division(poly: &BigPolynomial, den: &BigInt) -> BigPolynomial {
     let coefficients = poly
         .coefficients
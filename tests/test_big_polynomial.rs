@@ -0,0 +1,126 @@
+use implementing_fhez::*;
+
+fn poly(coefficients: &[i64]) -> BigPolynomial {
+    BigPolynomial {
+        coefficients: coefficients.iter().map(|&c| BigInt::from(c)).collect(),
+    }
+}
+
+// checks that a * a_inv = 1 in Z_m[X] / <X^n + 1>
+fn assert_is_inverse(a: &BigPolynomial, a_inv: &BigPolynomial, modulus: &BigInt) {
+    let product = (a * a_inv) % modulus;
+    let mut one = BigPolynomial::new(a.degree());
+    one.coefficients[0] = BigInt::one();
+    assert_eq!(
+        product, one,
+        "a * a_inv = {:?} mod {modulus}",
+        product.coefficients
+    );
+    assert!(
+        a_inv
+            .coefficients
+            .iter()
+            .all(|c| !c.is_negative() && c < modulus),
+        "inverse coefficients must be reduced mod {modulus}"
+    );
+}
+
+#[test]
+fn test_inverse_mod_prime() {
+    let modulus = BigInt::from(1048583);
+    let a = poly(&[3, 1, 4, 1, 5, 9, 2, 6]);
+    let a_inv = a.inverse_mod(&modulus).expect("a must be invertible");
+    assert_is_inverse(&a, &a_inv, &modulus);
+}
+
+#[test]
+fn test_inverse_mod_constant() {
+    let modulus = BigInt::from(38003);
+    let a = poly(&[3, 0, 0, 0]);
+    let a_inv = a
+        .inverse_mod(&modulus)
+        .expect("3 must be invertible mod 38003");
+    assert_eq!(a_inv, poly(&[12668, 0, 0, 0]));
+}
+
+// leading coefficients 2 and 3 are zero divisors mod 12, so the modulus must be split
+#[test]
+fn test_inverse_mod_composite_with_zero_divisors() {
+    let modulus = BigInt::from(12);
+    for coefficients in [[1, 0, 0, 2], [5, 1, 0, 3], [1, 2, 4, 6]] {
+        let a = poly(&coefficients);
+        let a_inv = a
+            .inverse_mod(&modulus)
+            .unwrap_or_else(|| panic!("{coefficients:?} must be invertible mod 12"));
+        assert_is_inverse(&a, &a_inv, &modulus);
+    }
+}
+
+// a prime power modulus can only be handled by Hensel lifting
+#[test]
+fn test_inverse_mod_prime_power() {
+    for modulus in [BigInt::from(1u64 << 40), BigInt::from(3).pow(30)] {
+        let a = poly(&[1, 6, 0, 0, 0, 0, 0, 12]);
+        let a_inv = a.inverse_mod(&modulus).expect("a must be invertible");
+        assert_is_inverse(&a, &a_inv, &modulus);
+    }
+}
+
+#[test]
+fn test_inverse_mod_not_invertible() {
+    // X + 1 divides X^n + 1 mod 2, so it is not invertible mod any even modulus
+    let x_plus_1 = poly(&[1, 1, 0, 0]);
+    assert!(!x_plus_1.is_invertible_mod(&BigInt::from(2)));
+    assert!(!x_plus_1.is_invertible_mod(&BigInt::from(12)));
+    assert!(!x_plus_1.is_invertible_mod(&BigInt::from(1u64 << 40)));
+    assert!(x_plus_1.is_invertible_mod(&BigInt::from(15)));
+
+    // The content 3 is not a unit mod 12
+    assert!(!poly(&[3, 6, 0, 9]).is_invertible_mod(&BigInt::from(12)));
+    assert!(!BigPolynomial::new(4).is_invertible_mod(&BigInt::from(7)));
+    assert!(!poly(&[7, 14, 0, 0]).is_invertible_mod(&BigInt::from(7)));
+}
+
+#[test]
+fn test_inverse_mod_random_gahe_modulus() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let mut rng = rand::thread_rng();
+    let p = sample_prime(ctx.eta, &mut rng);
+    let q0 = sample_prime(ctx.gamma - ctx.eta, &mut rng);
+    let x0 = &p * &q0;
+
+    let a = BigPolynomial {
+        coefficients: (0..ctx.n)
+            .map(|_| rng.gen_bigint_range(&BigInt::zero(), &x0))
+            .collect(),
+    };
+    let a_inv = a.inverse_mod(&x0).expect("random a is invertible w.h.p.");
+    assert_is_inverse(&a, &a_inv, &x0);
+
+    // The inverse mod x0 is also the inverse mod each of its factors
+    assert_is_inverse(&(&a % &p), &(&a_inv % &p), &p);
+    assert_is_inverse(&(&a % &q0), &(&a_inv % &q0), &q0);
+}
+
+#[test]
+fn test_mul_by_monomial_matches_schoolbook() {
+    // X * (1 + 2X + 3X^2 + 4X^3) = - 4 + X + 2X^2 + 3X^3
+    assert_eq!(poly(&[1, 2, 3, 4]).mul_by_monomial(1), poly(&[-4, 1, 2, 3]));
+    assert_eq!(
+        poly(&[1, 2, 3, 4]).mul_by_monomial(-1),
+        poly(&[2, 3, 4, -1])
+    );
+
+    let n = 16;
+    let a = BigPolynomial::rand(n, 64, n as u32);
+    for k in [0i64, 3, -3, 15, 16, 17, -16, -17, 31, 32, 100, -100] {
+        // X^k as a polynomial of degree below n, reduced by module
+        let mut monomial = BigPolynomial::new(k.rem_euclid(2 * n as i64) as usize + 1);
+        *monomial.coefficients.last_mut().unwrap() = BigInt::one();
+        let monomial = monomial.module(n);
+
+        assert_eq!(a.mul_by_monomial(k), &a * &monomial, "X^{k}");
+    }
+    assert_eq!(a.mul_by_monomial(5).mul_by_monomial(-5), a);
+}
@@ -0,0 +1,320 @@
+use implementing_fhez::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+struct Setup {
+    params: FhezParameters,
+    ctx: GaheContext,
+    sk: GaheSecretKey,
+    key: BootstrappingKey,
+    dcrt_ctx: DcrtContext,
+}
+
+fn setup() -> Setup {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let mut rng = rand::thread_rng();
+    let sk = GaheSecretKey::generate(&ctx, &mut rng);
+    let dcrt_ctx = DcrtContext::new(&params).unwrap();
+    let key = BootstrappingKey::generate(&sk, &ctx, &dcrt_ctx, &params, &mut rng);
+    Setup {
+        params,
+        ctx,
+        sk,
+        key,
+        dcrt_ctx,
+    }
+}
+
+// message with the bit in the constant coefficient and random bits elsewhere
+fn message_with_constant(bit: u64, n: usize) -> BigPolynomial {
+    let mut rng = rand::thread_rng();
+    let mut coefficients: Vec<BigInt> = (0..n).map(|_| BigInt::from(rng.gen_range(0..2))).collect();
+    coefficients[0] = BigInt::from(bit);
+    BigPolynomial { coefficients }
+}
+
+// absolute value of the noise in the constant coefficient
+fn constant_noise(s: &Setup, ciphertext: &ScalarCiphertext) -> BigInt {
+    noise_of(&s.sk, &s.ctx, ciphertext)
+        .unwrap()
+        .noise
+        .coefficients[0]
+        .abs()
+}
+
+#[test]
+fn test_bootstrap_refreshes_both_bits() {
+    let s = setup();
+
+    for bit in [0u64, 1, 1, 0] {
+        let message = message_with_constant(bit, s.params.n);
+        let ciphertext = encrypt_scalar::<BigPolynomial>(&s.sk, &s.ctx, &message, &mut ());
+
+        let refreshed = bootstrap(&ciphertext, &s.key, &s.dcrt_ctx, &s.params)
+            .expect("the ciphertext matches the bootstrapping key");
+        let decrypted =
+            decrypt_scalar::<BigPolynomial>(&s.sk, &s.ctx, &refreshed, &mut ()).unwrap();
+
+        assert_eq!(
+            decrypted.coefficients[0],
+            BigInt::from(bit),
+            "Bootstrapping changed the bit {bit}"
+        );
+    }
+}
+
+// only binary messages can be bootstrapped
+#[test]
+fn test_bootstrap_rejects_other_message_moduli() {
+    let s = setup();
+    let ciphertext = encrypt_scalar::<BigPolynomial>(
+        &s.sk,
+        &s.ctx,
+        &message_with_constant(1, s.params.n),
+        &mut (),
+    );
+
+    let params = FhezParameters { t: 3, ..s.params };
+    assert_eq!(
+        bootstrap(&ciphertext, &s.key, &s.dcrt_ctx, &params),
+        Err(GaheError::UnsupportedPlaintextModulus { t: 3 })
+    );
+}
+
+// the Dcrt forms of the key mean nothing under another backend
+#[test]
+fn test_bootstrap_rejects_other_contexts() {
+    let s = setup();
+    let ciphertext = encrypt_scalar::<BigPolynomial>(
+        &s.sk,
+        &s.ctx,
+        &message_with_constant(1, s.params.n),
+        &mut (),
+    );
+    let ntt_ctx = DcrtContext::with_backend(&s.params, DcrtBackend::Ntt).unwrap();
+
+    assert_eq!(s.key.context_id, s.dcrt_ctx.id());
+    assert!(matches!(
+        BlindRotation::new(&s.key, &ntt_ctx, &s.params),
+        Err(GaheError::ContextMismatch)
+    ));
+    assert_eq!(
+        bootstrap(&ciphertext, &s.key, &ntt_ctx, &s.params),
+        Err(GaheError::ContextMismatch)
+    );
+}
+
+// a ciphertext close to the decryption bound comes out with the same noise as a fresh one
+#[test]
+fn test_bootstrap_noise_independent_of_input() {
+    let s = setup();
+    let n = s.params.n;
+    let alpha = round_bigint_division(&s.sk.p, &s.ctx.t);
+    let p_over_4t = &s.sk.p / (BigInt::from(4u32) * &s.ctx.t);
+
+    for bit in [0u64, 1] {
+        let message = message_with_constant(bit, n);
+
+        // (p * q + r + alpha * m) * k mod x0 with the constant noise at p / 4t
+        let mut r = sample_poly_signed_bound(&(BigInt::one() << s.ctx.rho), n);
+        r.coefficients[0] = p_over_4t.clone();
+        let q = sample_poly_uniform_bound(&(&s.sk.x0 / &s.sk.p), n);
+        let inner = &(&(&s.sk.p * &q) + &r) + &(&alpha * &message);
+        let noisy = ScalarCiphertext::new(inner * &s.sk.k, &s.sk.x0, s.sk.fingerprint(&s.ctx));
+        assert!(constant_noise(&s, &noisy) >= p_over_4t);
+
+        let refreshed = bootstrap(&noisy, &s.key, &s.dcrt_ctx, &s.params)
+            .expect("the ciphertext matches the bootstrapping key");
+        let decrypted =
+            decrypt_scalar::<BigPolynomial>(&s.sk, &s.ctx, &refreshed, &mut ()).unwrap();
+        assert_eq!(decrypted.coefficients[0], BigInt::from(bit));
+
+        // Each of the gamma - eta external products adds at most l * n * b / 2 * 2^rho
+        let bound = BigInt::from_f64(NoiseModel::new(&s.params).bootstrap()).unwrap();
+        let noise = constant_noise(&s, &refreshed);
+        assert!(
+            noise < bound && noise < p_over_4t,
+            "Refreshed noise {noise} above the bound {bound}"
+        );
+    }
+}
+
+// a programmable test polynomial 2w is read at the secret exponent sum a_i * s_i
+#[test]
+fn test_blind_rotation_reads_the_test_polynomial() {
+    let s = setup();
+    let n = s.params.n;
+    let rotation = BlindRotation::new(&s.key, &s.dcrt_ctx, &s.params).unwrap();
+    let two = BigInt::from(2);
+    let w = message_with_constant(1, n);
+    let test_polynomial = &two * &w;
+
+    // Without rotation the accumulator is the encoding of the test polynomial
+    let zeros = vec![0; rotation.key.bit_keys.len()];
+    let encoded = rotation.run(&test_polynomial, &zeros).unwrap();
+    assert_eq!(
+        decrypt_scalar::<BigPolynomial>(&s.sk, &s.ctx, &encoded, &mut ()).unwrap(),
+        w
+    );
+
+    // The secret bits are those of q0 = x0 / p
+    let q0 = &s.sk.x0 / &s.sk.p;
+    let mut rng = rand::thread_rng();
+    let exponents: Vec<usize> = (0..rotation.key.bit_keys.len())
+        .map(|_| rng.gen_range(0..2 * n))
+        .collect();
+    let e: usize = exponents
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| q0.bit(*i as u64))
+        .map(|(_, a_i)| a_i)
+        .sum();
+
+    let rotated = rotation.run(&test_polynomial, &exponents).unwrap();
+    let decrypted = decrypt_scalar::<BigPolynomial>(&s.sk, &s.ctx, &rotated, &mut ()).unwrap();
+    // X^(- e) negates the coefficients that wrap around, which is the same bit mod 2
+    let expected = w.mul_by_monomial(-(e as i64)) % &two;
+    assert_eq!(decrypted.coefficients[0], expected.coefficients[0]);
+    assert_eq!(decrypted, expected);
+
+    assert!(matches!(
+        rotation.run(&test_polynomial, &exponents[1..]),
+        Err(GaheError::LengthMismatch { .. })
+    ));
+}
+
+// the same seed gives the same key, and the evaluation form matches the coefficient form
+#[test]
+fn test_bootstrapping_key_generation_and_footprint() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let dcrt_ctx = DcrtContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+
+    let key = BootstrapKeyCoefficients::generate(&sk, &ctx, &params, &mut StdRng::seed_from_u64(7));
+    let again =
+        BootstrapKeyCoefficients::generate(&sk, &ctx, &params, &mut StdRng::seed_from_u64(7));
+    assert_eq!(key.bit_keys, again.bit_keys);
+    assert_eq!(key.test_vector_key, again.test_vector_key);
+
+    let q0 = &sk.x0 / &sk.p;
+    assert_eq!(key.bit_keys.len() as u64, q0.bits());
+    for (i, bit_key) in key.bit_keys.iter().enumerate() {
+        let bit = decrypt_vector(&sk, &ctx, &params, bit_key).unwrap();
+        assert_eq!(bit.coefficients[0], BigInt::from(q0.bit(i as u64) as u8));
+    }
+
+    let coefficient_footprint = key.footprint();
+    let evaluation = BootstrappingKey::from_coefficients(key.clone(), &dcrt_ctx, &params);
+    let footprint = evaluation.footprint();
+    assert_eq!(
+        footprint.extraction_key,
+        coefficient_footprint.extraction_key
+    );
+    assert!(footprint.total() > footprint.extraction_key);
+
+    // Each Dcrt form is one layer of values per prime
+    let dcrt = &evaluation.bit_keys[0][0];
+    let per_ciphertext = dcrt.size_in_bytes();
+    assert!(per_ciphertext > 0);
+    assert_eq!(
+        footprint.bit_keys,
+        per_ciphertext * key.bit_keys.len() * params.l
+    );
+    for (dcrt, c) in evaluation
+        .test_vector_key
+        .iter()
+        .zip(key.test_vector_key.iter())
+    {
+        assert_eq!(&from_dcrt(&mut dcrt.clone(), &dcrt_ctx, &params), c);
+    }
+}
+
+// representative of a mod m in (- m / 2, m / 2]
+fn centered(a: &BigInt, m: &BigInt) -> BigInt {
+    let r = a.rem_euclid(m);
+    if &r * 2 > *m {
+        r - m
+    } else {
+        r
+    }
+}
+
+// each switched coefficient c' satisfies |c' / q - c / x0| <= 1 / 2q mod 1
+#[test]
+fn test_mod_switch_scalar_ciphertext() {
+    let s = setup();
+    let message = message_with_constant(1, s.params.n);
+    let ciphertext = encrypt_scalar::<BigPolynomial>(&s.sk, &s.ctx, &message, &mut ());
+
+    for q in [BigInt::from(2 * s.params.n), BigInt::one() << 40u32] {
+        let switched = ciphertext.mod_switch(&q);
+        assert_eq!(switched.modulus, q);
+        assert_eq!(switched.n, ciphertext.n);
+        let q_x0 = &q * &s.sk.x0;
+        for (c, c_prime) in ciphertext
+            .poly
+            .coefficients
+            .iter()
+            .zip(switched.poly.coefficients.iter())
+        {
+            assert!(!c_prime.is_negative() && c_prime < &q);
+            let rounding = centered(&(&s.sk.x0 * c_prime - &q * c), &q_x0);
+            assert!(rounding.abs() * 2 <= s.sk.x0);
+        }
+    }
+}
+
+// the switched bits read the phase of the extracted ciphertext up to the analytic bound
+#[test]
+fn test_mod_switch_bits_noise_within_bound() {
+    let s = setup();
+    let p = &s.sk.p;
+    let q0 = &s.sk.x0 / p;
+    let alpha = round_bigint_division(p, &s.ctx.t);
+    let set_bits = (0..q0.bits()).filter(|&i| q0.bit(i)).count();
+    assert_eq!(s.key.bit_keys.len() as u64, q0.bits());
+    assert!(set_bits as f64 / 2.0 <= NoiseModel::new(&s.params).mod_switch());
+
+    for bit in [0u64, 1, 1, 0] {
+        let message = message_with_constant(bit, s.params.n);
+        let ciphertext = encrypt_scalar::<BigPolynomial>(&s.sk, &s.ctx, &message, &mut ());
+
+        // The extracted integer ciphertext has the constant coefficient as its phase
+        let c = s.key.extract(&ciphertext, &s.params).unwrap();
+        let phase = c.rem_euclid(p);
+        let noise = centered(&(&phase - &alpha * bit), p);
+        assert!(noise.abs() * 4 < *p);
+
+        for q in [
+            BigInt::from(2 * s.params.n),
+            BigInt::one() << 20u32,
+            BigInt::one() << 40u32,
+        ] {
+            let switched = mod_switch_bits(&c, &s.sk.x0, q0.bits() as usize, &q);
+            let sum: BigInt = switched
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| q0.bit(*i as u64))
+                .map(|(_, a_i)| a_i)
+                .sum();
+
+            // p * sum - q * phase is p times the rounding noise, mod p * q
+            let rounding = centered(&(p * &sum - &q * &phase), &(p * &q));
+            assert!(
+                rounding.abs() * 2 <= p * set_bits,
+                "Rounding {rounding} above the bound for q = {q}"
+            );
+
+            // round(t * sum / q) mod t decodes the bit
+            let decoded = round_bigint_division(&(&sum * &s.ctx.t), &q).rem_euclid(&s.ctx.t);
+            assert_eq!(decoded, BigInt::from(bit));
+        }
+    }
+
+    let other = ScalarCiphertext::new(BigPolynomial::new(s.params.n), &s.sk.p, s.key.fingerprint);
+    assert!(matches!(
+        s.key.extract(&other, &s.params),
+        Err(GaheError::ModulusMismatch)
+    ));
+}
@@ -0,0 +1,384 @@
+use implementing_fhez::*;
+
+fn ntt_setup(params: &FhezParameters) -> DcrtContext {
+    DcrtContext::with_backend(params, DcrtBackend::Ntt).unwrap()
+}
+
+#[test]
+fn test_ntt_table_roundtrip() {
+    for n in [2, 16, 1024] {
+        let table = NttTable::new(NTT_PRIMES_30[0], n).unwrap();
+        let mut rng = rand::thread_rng();
+        let original: Vec<u64> = (0..n).map(|_| rng.gen_range(0..table.prime)).collect();
+
+        let mut values = original.clone();
+        table.forward(&mut values);
+        assert_ne!(values, original);
+        table.inverse(&mut values);
+        assert_eq!(values, original);
+    }
+
+    // 2n = 2^18 does not divide p - 1 = 2^17 * 7961
+    assert!(NttTable::new(1043464193, 1 << 17).is_none());
+    assert!(NttTable::new(PRIMES_20[0], 16).is_none());
+}
+
+#[test]
+fn test_ntt_dcrt_roundtrip() {
+    let params = FhezParameters::article_line1();
+    let context = ntt_setup(&params);
+    let poly = BigPolynomial::rand(params.n, 200, params.n as u32);
+
+    let mut dcrt = to_dcrt(&poly, &context, &params);
+    assert!(matches!(dcrt.poly, DcrtLayers::Ntt { .. }));
+    assert_eq!(from_dcrt(&mut dcrt, &context, &params), poly);
+}
+
+#[test]
+fn test_ntt_operations_match_big_polynomial() {
+    let params = FhezParameters::small_test();
+    let context = ntt_setup(&params);
+    let a = BigPolynomial::rand(params.n, 100, params.n as u32);
+    let b = BigPolynomial::rand(params.n, 60, params.n as u32);
+    let a_dcrt = to_dcrt(&a, &context, &params);
+    let b_dcrt = to_dcrt(&b, &context, &params);
+
+    let mut sum = &a_dcrt + &b_dcrt;
+    assert_eq!(from_dcrt(&mut sum, &context, &params), &a + &b);
+
+    let mut product = &a_dcrt * &b_dcrt;
+    assert_eq!(from_dcrt(&mut product, &context, &params), &a * &b);
+
+    let mut accumulated = a_dcrt.clone();
+    accumulated *= b_dcrt.clone();
+    accumulated += a_dcrt.clone();
+    let mut inner = inner_product(
+        &[a_dcrt.clone(), a_dcrt],
+        &[b_dcrt, Dcrt::new_ntt(params.n, context.primes)],
+    );
+    assert_eq!(
+        from_dcrt(&mut accumulated, &context, &params),
+        &(&a * &b) + &a
+    );
+    assert_eq!(from_dcrt(&mut inner, &context, &params), &a * &b);
+}
+
+// at n = 1024 the FFT products of 20 bit primes no longer fit in an f64 but the NTT is exact
+#[test]
+fn test_ntt_exact_beyond_fft_precision() {
+    let params = FhezParameters {
+        n: 1024,
+        ..FhezParameters::small_test()
+    };
+    assert!(matches!(
+        DcrtContext::new(&params),
+        Err(ParamError::FftPrecision { .. })
+    ));
+
+    let context = ntt_setup(&params);
+    let a = BigPolynomial::rand(params.n, 140, params.n as u32);
+    let b = BigPolynomial::rand(params.n, 20, params.n as u32);
+    let mut product = &to_dcrt(&a, &context, &params) * &to_dcrt(&b, &context, &params);
+    assert_eq!(from_dcrt(&mut product, &context, &params), &a * &b);
+}
+
+#[test]
+#[should_panic(expected = "Mismatch in the Dcrt backends")]
+fn test_mixed_backends_panic() {
+    let params = FhezParameters::small_test();
+    let ntt_context = ntt_setup(&params);
+    let fft_context = DcrtContext::new(&params).unwrap();
+    let poly = BigPolynomial::rand(params.n, 20, params.n as u32);
+
+    let _ = &to_dcrt(&poly, &ntt_context, &params) + &to_dcrt(&poly, &fft_context, &params);
+}
+
+fn random_residues(
+    n: usize,
+    count: usize,
+    context: &DcrtContext,
+    params: &FhezParameters,
+) -> Vec<Dcrt> {
+    (0..count)
+        .map(|_| to_dcrt(&BigPolynomial::rand(n, 200, n as u32), context, params))
+        .collect()
+}
+
+// the measured error of an external product sized inner product stays below the estimate
+#[test]
+fn test_checked_conversion_reports_error() {
+    let params = FhezParameters::small_test();
+    let context = DcrtContext::new(&params).unwrap();
+    let a = random_residues(params.n, params.l, &context, &params);
+    let b = random_residues(params.n, params.l, &context, &params);
+
+    let mut expected = inner_product(&a, &b);
+    let expected = from_dcrt(&mut expected, &context, &params);
+    let (poly, report) = from_dcrt_checked(
+        &mut inner_product(&a, &b),
+        &context,
+        &params,
+        DEFAULT_PRECISION_THRESHOLD,
+    )
+    .unwrap();
+    assert_eq!(poly, expected);
+
+    let prime = *context.primes.iter().max().unwrap();
+    let bound = fft_error_estimate(params.n, prime, prime as f64, prime as f64, params.l);
+    assert!(report.within(DEFAULT_PRECISION_THRESHOLD));
+    assert!(report.max_rounding_error <= bound);
+
+    // Small operands are far from the mantissa limit
+    assert!(fft_error_estimate(params.n, prime, 2.0, 1e3, 1) < 1e-9);
+}
+
+#[test]
+fn test_checked_conversion_detects_precision_loss() {
+    // Passes validate, but 16 products of full residues at n = 1024 need 54 bits
+    let params = FhezParameters {
+        n: 1024,
+        b: 1 << 38,
+        l: 4,
+        ..FhezParameters::small_test()
+    };
+    let context = DcrtContext::new(&params).unwrap();
+    let a = random_residues(params.n, 16, &context, &params);
+    let b = random_residues(params.n, 16, &context, &params);
+
+    let prime = *context.primes.iter().max().unwrap();
+    assert!(fft_error_estimate(params.n, prime, prime as f64, prime as f64, 16) > 0.5);
+    let err = from_dcrt_checked(
+        &mut inner_product(&a, &b),
+        &context,
+        &params,
+        DEFAULT_PRECISION_THRESHOLD,
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, DcrtError::PrecisionLoss { rounding_error, .. } if rounding_error > 0.25)
+    );
+
+    // A value that is not finite never passes
+    let mut corrupted = a[0].clone();
+    if let DcrtLayers::Fft { layers, .. } = &mut corrupted.poly {
+        layers[0][3] = c64::new(f64::NAN, 0.0);
+    }
+    let err = from_dcrt_checked(&mut corrupted, &context, &params, 0.49).unwrap_err();
+    assert!(
+        matches!(err, DcrtError::PrecisionLoss { rounding_error, .. } if rounding_error.is_infinite())
+    );
+
+    // Nor does a value beyond 2^52, whose rounding can no longer be measured
+    let mut corrupted = a[0].clone();
+    if let DcrtLayers::Fft { layers, .. } = &mut corrupted.poly {
+        layers[0][3] = c64::new(2f64.powi(70), 0.0);
+    }
+    let err = from_dcrt_checked(&mut corrupted, &context, &params, 0.49).unwrap_err();
+    assert!(
+        matches!(err, DcrtError::PrecisionLoss { rounding_error, .. } if rounding_error.is_infinite())
+    );
+
+    // The NTT backend is exact on the same products
+    let context = DcrtContext::with_backend(&params, DcrtBackend::Ntt).unwrap();
+    let a = random_residues(params.n, 16, &context, &params);
+    let b = random_residues(params.n, 16, &context, &params);
+    let (_, report) = from_dcrt_checked(&mut inner_product(&a, &b), &context, &params, 0.0)
+        .unwrap_or_else(|_| panic!("NTT layers have no rounding"));
+    assert_eq!(report, PrecisionReport::default());
+}
+
+// the FFT context carries its plan and twiddles, the NTT context only its tables
+#[test]
+fn test_context_owns_the_transforms() {
+    let params = FhezParameters::small_test();
+    let context = DcrtContext::new(&params).unwrap();
+    let tables = context.fft.as_ref().expect("FFT contexts plan the FFT");
+    // The layers are folded to n / 2 complex values
+    let half = params.n / 2;
+    assert_eq!(tables.plan.fft_size(), half);
+    assert_eq!(tables.twiddles.len(), half);
+    let eighth_turn = c64::new(1.0, 1.0) / 2f64.sqrt();
+    assert!((tables.twiddles[half / 2] - eighth_turn).norm() < 1e-15);
+    assert!((tables.inv_twiddles[0] * half as f64 - c64::new(1.0, 0.0)).norm() < 1e-15);
+
+    let poly = BigPolynomial::rand(params.n, 100, params.n as u32);
+    for _ in 0..3 {
+        let mut dcrt = to_dcrt(&poly, &context, &params);
+        assert_eq!(from_dcrt(&mut dcrt, &context, &params), poly);
+    }
+
+    let context = ntt_setup(&params);
+    assert!(context.fft.is_none());
+    assert_eq!(context.ntt_tables.len(), context.primes.len());
+}
+
+// the parallel mode gives bit for bit the results of the serial path on both backends
+#[test]
+fn test_parallel_matches_serial() {
+    let params = FhezParameters::small_test();
+    for backend in [DcrtBackend::Fft, DcrtBackend::Ntt] {
+        let context = DcrtContext::with_backend(&params, backend).unwrap();
+        let vector: Vec<BigPolynomial> = (0..params.l)
+            .map(|_| BigPolynomial::rand(params.n, 150, params.n as u32))
+            .collect();
+        let scalar = BigPolynomial::rand(params.n, 150, params.n as u32);
+
+        let run = |context: &DcrtContext| {
+            let vector: Vec<Dcrt> = vector
+                .iter()
+                .map(|p| to_dcrt(p, context, &params))
+                .collect();
+            let mut scalar = to_dcrt(&scalar, context, &params);
+            let mut product = external_product(
+                &vector,
+                &mut scalar,
+                &params,
+                &mut DcrtRing {
+                    context,
+                    params: &params,
+                },
+            );
+            let layers = product.poly.clone();
+            (layers, from_dcrt(&mut product, context, &params))
+        };
+
+        // The same context keeps the FFT plan, so only the mode changes
+        let serial = run(&context);
+        let context = context.with_parallelism(true);
+        let parallel = run(&context);
+        assert_eq!(parallel, serial);
+        assert!(to_dcrt(&scalar, &context, &params).parallel);
+    }
+}
+
+// subtraction, negation and constants stay in the Dcrt form on both backends
+#[test]
+fn test_sub_neg_and_constants_roundtrip() {
+    let params = FhezParameters::small_test();
+    for backend in [DcrtBackend::Fft, DcrtBackend::Ntt] {
+        let context = DcrtContext::with_backend(&params, backend).unwrap();
+        let a = BigPolynomial::rand(params.n, 100, params.n as u32);
+        let b = BigPolynomial::rand(params.n, 60, params.n as u32);
+        let a_dcrt = to_dcrt(&a, &context, &params);
+        let b_dcrt = to_dcrt(&b, &context, &params);
+        let back = |mut x: Dcrt| from_dcrt(&mut x, &context, &params);
+
+        assert_eq!(back(&a_dcrt - &b_dcrt), &a - &b);
+        assert_eq!(back(a_dcrt.clone() - b_dcrt.clone()), &a - &b);
+        assert_eq!(back(-&a_dcrt), &BigPolynomial::new(params.n) - &a);
+        assert_eq!(
+            back(-(a_dcrt.clone() + &b_dcrt)),
+            &BigPolynomial::new(params.n) - &(&a + &b)
+        );
+
+        let mut accumulated = a_dcrt.clone();
+        accumulated -= &b_dcrt;
+        accumulated += b_dcrt.clone();
+        accumulated *= &b_dcrt;
+        assert_eq!(back(accumulated), &a * &b);
+
+        // Constants of any size, negative ones included, are reduced mod each prime
+        for c in [0i64, 1, -1, 7, -12345, i64::MAX, i64::MIN] {
+            assert_eq!(back(&a_dcrt * c), &BigInt::from(c) * &a);
+        }
+        let mut scaled = b_dcrt.clone();
+        scaled *= -3;
+        assert_eq!(back(scaled), &BigInt::from(-3) * &b);
+
+        // c * X^k wraps around with a sign change every n
+        for k in [0, 1, params.n - 1, params.n, params.n + 5, 2 * params.n + 3] {
+            let monomial = Dcrt::monomial(5, k, &context, &params);
+            let mut expected = BigPolynomial::new(params.n);
+            let sign = if (k / params.n).is_multiple_of(2) {
+                5
+            } else {
+                -5
+            };
+            expected.coefficients[k % params.n] = BigInt::from(sign);
+            assert_eq!(back(monomial.clone()), expected);
+            assert_eq!(back(&a_dcrt * &monomial), &a * &expected);
+        }
+        let monomial = Dcrt::monomial(i64::MIN, params.n + 1, &context, &params);
+        let mut expected = BigPolynomial::new(params.n);
+        expected.coefficients[1] = -BigInt::from(i64::MIN);
+        assert_eq!(back(monomial), expected);
+    }
+}
+
+// multiplying by X^k in the Dcrt form matches the negacyclic shift of the coefficients
+#[test]
+fn test_dcrt_mul_by_monomial() {
+    let params = FhezParameters::small_test();
+    let n = params.n as i64;
+    for backend in [DcrtBackend::Fft, DcrtBackend::Ntt] {
+        let context = DcrtContext::with_backend(&params, backend).unwrap();
+        let a = BigPolynomial::rand(params.n, 150, params.n as u32);
+        let a_dcrt = to_dcrt(&a, &context, &params);
+
+        for k in [0, 1, -1, n - 1, n, n + 7, -n - 7, 2 * n, 5 * n + 3] {
+            let mut rotated = a_dcrt.mul_by_monomial(k, &context);
+            assert_eq!(
+                from_dcrt(&mut rotated, &context, &params),
+                a.mul_by_monomial(k),
+                "X^{k} with {backend:?}"
+            );
+        }
+    }
+}
+
+// the limb decomposition gives the same Dcrt polynomials as the BigInt round trip
+#[test]
+fn test_gadget_decompose_dcrt_matches_bigint_path() {
+    let not_power_of_two = FhezParameters {
+        b: 1_000_000,
+        ..FhezParameters::small_test()
+    };
+    for (params, backend) in [
+        (FhezParameters::small_test(), DcrtBackend::Fft),
+        (FhezParameters::small_test(), DcrtBackend::Ntt),
+        (FhezParameters::article_line1(), DcrtBackend::Ntt),
+        (not_power_of_two, DcrtBackend::Fft),
+    ] {
+        let context = DcrtContext::with_backend(&params, backend).unwrap();
+        // Coefficients of both signs around the size of the ciphertexts
+        let scalar = BigPolynomial::rand(params.n, params.gamma as u32, params.n as u32);
+        let scalar_dcrt = to_dcrt(&scalar, &context, &params);
+
+        let expected: Vec<Dcrt> = gadget_decompose(&scalar, &params)
+            .iter()
+            .map(|digits| to_dcrt(digits, &context, &params))
+            .collect();
+        let decomposed = gadget_decompose_dcrt(&mut scalar_dcrt.clone(), &context, &params);
+        assert_eq!(decomposed, expected, "b = {} with {backend:?}", params.b);
+
+        // Reducing first by a modulus smaller than the coefficients
+        let modulus = (BigInt::one() << (params.gamma as u32 - 20)) + 1u32;
+        let expected: Vec<Dcrt> = gadget_decompose(&(&scalar % &modulus), &params)
+            .iter()
+            .map(|digits| to_dcrt(digits, &context, &params))
+            .collect();
+        let decomposed =
+            gadget_decompose_dcrt_mod(&mut scalar_dcrt.clone(), &modulus, &context, &params);
+        assert_eq!(decomposed, expected, "b = {} with {backend:?}", params.b);
+    }
+}
+
+// the reduction in the Dcrt form matches the reduction of the coefficients
+#[test]
+fn test_dcrt_rem_euclid() {
+    let params = FhezParameters::small_test();
+    for backend in [DcrtBackend::Fft, DcrtBackend::Ntt] {
+        let context = DcrtContext::with_backend(&params, backend).unwrap();
+        let a = BigPolynomial::rand(params.n, 170, params.n as u32);
+        let a_dcrt = to_dcrt(&a, &context, &params);
+
+        for modulus in [BigInt::from(1_000_003), (BigInt::one() << 150u32) - 3u32] {
+            let mut reduced = a_dcrt.rem_euclid(&modulus, &context, &params);
+            assert_eq!(
+                from_dcrt(&mut reduced, &context, &params),
+                &a % &modulus,
+                "mod {modulus} with {backend:?}"
+            );
+        }
+    }
+}
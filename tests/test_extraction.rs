@@ -0,0 +1,147 @@
+use implementing_fhez::*;
+
+struct Setup {
+    params: FhezParameters,
+    ctx: GaheContext,
+    sk: GaheSecretKey,
+    key: ExtractionKey,
+}
+
+// t = 5 so that a coefficient extracted with the wrong sign decrypts to another message
+fn setup() -> Setup {
+    let params = FhezParameters {
+        t: 5,
+        ..FhezParameters::small_test()
+    };
+    let ctx = GaheContext::new(&params).unwrap();
+    let mut rng = rand::thread_rng();
+    let sk = GaheSecretKey::generate(&ctx, &mut rng);
+    let key = ExtractionKey::generate(&sk, &ctx, &params, &mut rng);
+    Setup {
+        params,
+        ctx,
+        sk,
+        key,
+    }
+}
+
+fn random_message(n: usize, t: u64) -> BigPolynomial {
+    let mut rng = rand::thread_rng();
+    BigPolynomial {
+        coefficients: (0..n).map(|_| BigInt::from(rng.gen_range(0..t))).collect(),
+    }
+}
+
+#[test]
+fn test_extract_decrypts_each_coefficient() {
+    let s = setup();
+    let n = s.params.n;
+    let extracted_key = s.sk.extracted(&s.ctx);
+    let model = NoiseModel::new(&s.params);
+    let bound = BigInt::from_f64(model.fresh() + model.gadget_noise()).unwrap();
+
+    let message = random_message(n, s.params.t);
+    let ciphertext = encrypt_scalar::<BigPolynomial>(&s.sk, &s.ctx, &message, &mut ());
+    for i in [0, 1, 2, n / 2, n - 1] {
+        let extracted = s.key.extract(&ciphertext, i, &s.params).unwrap();
+        assert_eq!(extracted.modulus, s.sk.x0);
+
+        let (decrypted, noise) =
+            decrypt_integer_with_noise(&extracted_key, &s.ctx, &extracted).unwrap();
+        assert_eq!(decrypted, message.coefficients[i], "coefficient {i}");
+        assert!(noise.abs() <= bound, "Noise {noise} of the coefficient {i}");
+    }
+}
+
+// the coefficient 0 of m * X^k is - m_(n - k), which decrypts to t - m_(n - k)
+#[test]
+fn test_extract_after_negacyclic_wrap() {
+    let s = setup();
+    let n = s.params.n;
+    let extracted_key = s.sk.extracted(&s.ctx);
+    let t = BigInt::from(s.params.t);
+
+    let mut message = random_message(n, s.params.t);
+    message.coefficients[n - 1] = BigInt::one();
+    message.coefficients[n - 3] = BigInt::from(3);
+    let ciphertext = encrypt_scalar::<BigPolynomial>(&s.sk, &s.ctx, &message, &mut ());
+
+    for k in [1, 3] {
+        let mut monomial = BigPolynomial::new(n);
+        monomial.coefficients[k] = BigInt::one();
+        let rotated = ciphertext.mul_plain(&monomial).unwrap();
+
+        // Coefficients from k on come from m_(i - k) without a sign change
+        let extracted = s.key.extract(&rotated, k + 1, &s.params).unwrap();
+        assert_eq!(
+            decrypt_integer(&extracted_key, &s.ctx, &extracted).unwrap(),
+            message.coefficients[1]
+        );
+
+        let extracted = s.key.extract(&rotated, 0, &s.params).unwrap();
+        let expected = (-&message.coefficients[n - k]).rem_euclid(&t);
+        assert_eq!(
+            decrypt_integer(&extracted_key, &s.ctx, &extracted).unwrap(),
+            expected
+        );
+        assert_ne!(expected, message.coefficients[n - k]);
+    }
+}
+
+#[test]
+fn test_extract_rejects_other_keys() {
+    let s = setup();
+    let other = GaheSecretKey::generate(&s.ctx, &mut rand::thread_rng());
+    let ciphertext = encrypt_scalar::<BigPolynomial>(
+        &other,
+        &s.ctx,
+        &random_message(s.params.n, s.params.t),
+        &mut (),
+    );
+    assert_eq!(
+        s.key.extract(&ciphertext, 0, &s.params),
+        Err(GaheError::ModulusMismatch)
+    );
+
+    let small = ScalarCiphertext::new(BigPolynomial::new(4), &s.sk.x0, s.key.fingerprint);
+    assert!(matches!(
+        s.key.extract(&small, 0, &s.params),
+        Err(GaheError::DegreeMismatch { .. })
+    ));
+
+    // A key sharing x0 only differs by its fingerprint
+    let sharing =
+        s.sk.generate_sharing_modulus(&s.ctx, &mut rand::thread_rng());
+    let ciphertext = encrypt_scalar::<BigPolynomial>(
+        &sharing,
+        &s.ctx,
+        &random_message(s.params.n, s.params.t),
+        &mut (),
+    );
+    assert_eq!(
+        s.key.extract(&ciphertext, 0, &s.params),
+        Err(GaheError::KeyMismatch {
+            left: sharing.fingerprint(&s.ctx),
+            right: s.key.fingerprint,
+        })
+    );
+
+    let own = encrypt_scalar::<BigPolynomial>(
+        &s.sk,
+        &s.ctx,
+        &random_message(s.params.n, s.params.t),
+        &mut (),
+    );
+    assert_eq!(
+        s.key.extract(&own, s.params.n, &s.params),
+        Err(GaheError::IndexOutOfRange {
+            index: s.params.n,
+            n: s.params.n,
+        })
+    );
+    let extracted = s.key.extract(&own, 0, &s.params).unwrap();
+    assert!(matches!(
+        decrypt_integer(&other.extracted(&s.ctx), &s.ctx, &extracted),
+        Err(GaheError::KeyMismatch { .. })
+    ));
+}
@@ -0,0 +1,110 @@
+use implementing_fhez::*;
+
+type Int = FixedInt<4>;
+
+fn random_bigint(bits: u64) -> BigInt {
+    rand::thread_rng().gen_bigint(bits)
+}
+
+fn fixed(value: &BigInt) -> Int {
+    Int::from_bigint(value).expect("the value fits in 4 limbs")
+}
+
+#[test]
+fn test_bigint_conversion_roundtrip() {
+    let max: BigInt = (BigInt::one() << 255u32) - 1u32;
+    for value in [
+        BigInt::zero(),
+        BigInt::from(-1),
+        BigInt::from(u64::MAX),
+        -BigInt::from(u64::MAX),
+        -&max,
+        max.clone(),
+    ] {
+        assert_eq!(fixed(&value).to_bigint(), value);
+    }
+    assert_eq!(Int::from_i64(-5).to_bigint(), BigInt::from(-5));
+
+    // 255 bits plus the sign is the largest value of 4 limbs
+    assert!(Int::from_bigint(&(BigInt::one() << 255)).is_none());
+    assert!(Int::from_bigint(&(BigInt::one() << 300)).is_none());
+}
+
+#[test]
+fn test_arithmetic_matches_bigint() {
+    for _ in 0..200 {
+        let a = random_bigint(120);
+        let b = random_bigint(120);
+        let (fa, fb) = (fixed(&a), fixed(&b));
+
+        assert_eq!((fa + fb).to_bigint(), &a + &b);
+        assert_eq!((fa - fb).to_bigint(), &a - &b);
+        assert_eq!((-fa).to_bigint(), -&a);
+        assert_eq!((fa * fb).to_bigint(), &a * &b);
+        assert_eq!(fa.cmp(&fb), a.cmp(&b));
+
+        let y = rand::thread_rng().gen::<u64>();
+        assert_eq!(fa.mul_u64(y).to_bigint(), &a * y);
+
+        // Shifts in both directions, the right one rounding toward minus infinity
+        for shift in [0, 1, 63, 64, 65, 100] {
+            assert_eq!((fa << shift).to_bigint(), &a << shift);
+            assert_eq!((fa >> shift).to_bigint(), &a >> shift);
+        }
+    }
+}
+
+#[test]
+fn test_rem_euclid_matches_bigint() {
+    for _ in 0..200 {
+        let m = random_bigint(100).abs() + 1;
+        let a = random_bigint(250);
+        assert_eq!(
+            fixed(&a).rem_euclid(&fixed(&m)).to_bigint(),
+            a.rem_euclid(&m)
+        );
+
+        let p = rand::thread_rng().gen_range(1..=u64::MAX);
+        assert_eq!(
+            BigInt::from(fixed(&a).rem_u64(p)),
+            a.rem_euclid(&BigInt::from(p))
+        );
+    }
+    let seven = Int::from_i64(7);
+    assert_eq!(Int::from_i64(-14).rem_euclid(&seven), Int::ZERO);
+    assert_eq!(Int::from_i64(-15).rem_euclid(&seven), Int::from_i64(6));
+}
+
+#[test]
+fn test_signed_digits_match_decomposition() {
+    let mut digits = [0i64; 12];
+    for b in [2u64, 1 << 11, 1 << 20, 1 << 38] {
+        for _ in 0..50 {
+            let value = random_bigint(200);
+            fixed(&value).signed_digits(b, &mut digits);
+            let expected = signed_base_b_decomposition(&value, b, digits.len());
+            let digits: Vec<BigInt> = digits.iter().map(|&d| BigInt::from(d)).collect();
+            assert_eq!(digits, expected, "b = {b}");
+        }
+    }
+}
+
+#[test]
+fn test_fixed_polynomial_matches_big_polynomial() {
+    let n = 32;
+    let a = BigPolynomial::rand(n, 100, n as u32);
+    let b = BigPolynomial::rand(n, 100, n as u32);
+    let fa = FixedPolynomial::<4>::from_big(&a).unwrap();
+    let fb = FixedPolynomial::<4>::from_big(&b).unwrap();
+    assert!(FixedPolynomial::<4>::products_fit(100, n));
+    assert!(!FixedPolynomial::<4>::products_fit(126, n));
+
+    assert_eq!((&fa + &fb).to_big(), &a + &b);
+    assert_eq!((&fa - &fb).to_big(), &a - &b);
+    assert_eq!((&fa * &fb).to_big(), &a * &b);
+    assert_eq!(fa.mul_by_monomial(-37).to_big(), a.mul_by_monomial(-37));
+
+    let m = BigInt::from(1_000_003);
+    assert_eq!(fa.rem_euclid(&fixed(&m)).to_big(), &a % &m);
+    assert!(FixedPolynomial::<1>::from_big(&a).is_none());
+}
@@ -20,12 +20,19 @@ fn make_test_key(n: usize) -> (GaheSecretKey, GaheContext) {
         coefficients: k_inv_coeffs,
     };
 
-    let sk = GaheSecretKey { p, k, k_inv, x0 };
+    let sk = GaheSecretKey {
+        p,
+        k,
+        k_inv,
+        x0,
+        key_id: 0,
+    };
     let ctx = GaheContext {
         t,
         n,
         gamma: 10,
         rho: 1,
+        eta: 5,
     };
     (sk, ctx)
 }
@@ -36,12 +43,12 @@ fn encrypt_deterministic(
     message: &BigPolynomial,
     q: &BigPolynomial,
     r: &BigPolynomial,
-) -> BigPolynomial {
+) -> ScalarCiphertext {
     let p_q_plus_r = (&sk.p * q) + r;
     let x = (p_q_plus_r * &sk.k) % &sk.x0;
     let alpha = round_bigint_division(&sk.p, &ctx.t);
     let msg_term = (message * &(&alpha * &sk.k)) % &sk.x0;
-    (&x + &msg_term) % &sk.x0
+    ScalarCiphertext::new(&x + &msg_term, &sk.x0, sk.fingerprint(ctx))
 }
 
 #[test]
@@ -74,7 +81,7 @@ fn test_encrypt_decrypt_roundtrip() {
     };
 
     let ciphertext = encrypt_deterministic(&sk, &ctx, &message, &q, &r);
-    let decrypted = decrypt_scalar(&sk, &ctx, &ciphertext);
+    let decrypted = decrypt_scalar::<BigPolynomial>(&sk, &ctx, &ciphertext, &mut ()).unwrap();
 
     assert_eq!(
         decrypted.coefficients, message.coefficients,
@@ -106,7 +113,7 @@ fn test_encrypt_decrypt_zero_message() {
     };
 
     let ciphertext = encrypt_deterministic(&sk, &ctx, &zero_msg, &q, &r);
-    let decrypted = decrypt_scalar(&sk, &ctx, &ciphertext);
+    let decrypted = decrypt_scalar::<BigPolynomial>(&sk, &ctx, &ciphertext, &mut ()).unwrap();
 
     assert!(
         decrypted.coefficients.iter().all(|c| c.is_zero()),
@@ -141,7 +148,7 @@ fn test_encrypt_decrypt_max_message() {
     };
 
     let ciphertext = encrypt_deterministic(&sk, &ctx, &message, &q, &r);
-    let decrypted = decrypt_scalar(&sk, &ctx, &ciphertext);
+    let decrypted = decrypt_scalar::<BigPolynomial>(&sk, &ctx, &ciphertext, &mut ()).unwrap();
 
     assert_eq!(
         decrypted.coefficients, message.coefficients,
@@ -186,10 +193,10 @@ fn test_ciphertext_internal_structure() {
         .collect();
 
     let ciphertext = encrypt_deterministic(&sk, &ctx, &message, &q, &r);
-    let c_prime = (ciphertext * &sk.k_inv) % &sk.x0;
+    let c_prime = (ciphertext.poly * &sk.k_inv) % &sk.x0;
 
-    for i in 0..n {
-        let expected = expected_inner[i].rem_euclid(&sk.x0);
+    for (i, inner) in expected_inner.iter().enumerate() {
+        let expected = inner.rem_euclid(&sk.x0);
         assert_eq!(
             c_prime.coefficients[i], expected,
             "Index {i} got {} but expected {} modulo x0",
@@ -212,8 +219,8 @@ fn test_random_encrypt_decrypt_multiple_rounds() {
             coefficients: msg_coeffs,
         };
 
-        let ciphertext = encrypt_scalar(&sk, &ctx, &message);
-        let decrypted = decrypt_scalar(&sk, &ctx, &ciphertext);
+        let ciphertext = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &message, &mut ());
+        let decrypted = decrypt_scalar::<BigPolynomial>(&sk, &ctx, &ciphertext, &mut ()).unwrap();
 
         assert_eq!(
             decrypted.coefficients, message.coefficients,
@@ -221,3 +228,577 @@ fn test_random_encrypt_decrypt_multiple_rounds() {
         );
     }
 }
+
+// checks the structure of a generated key at the small test parameters
+#[test]
+fn test_generate_secret_key_structure() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let mut rng = rand::thread_rng();
+    let sk = GaheSecretKey::generate(&ctx, &mut rng);
+
+    assert_eq!(sk.p.bits(), ctx.eta, "p must have exactly eta bits");
+    assert!(is_probable_prime(&sk.p, 40, &mut rng), "p must be prime");
+    assert!((&sk.x0 % &sk.p).is_zero(), "x0 must be a multiple of p");
+    assert!(sk.x0.bits() >= ctx.gamma - 1 && sk.x0.bits() <= ctx.gamma);
+    assert_eq!(sk.k.degree(), ctx.n);
+    assert_eq!(sk.k_inv.degree(), ctx.n);
+
+    let mut one = BigPolynomial::new(ctx.n);
+    one.coefficients[0] = BigInt::one();
+    assert_eq!(
+        (&sk.k * &sk.k_inv) % &sk.x0,
+        one,
+        "k * k_inv must be 1 mod x0"
+    );
+}
+
+// encrypts and decrypts random messages with generated keys at the article parameters
+#[test]
+fn test_generate_secret_key_roundtrip() {
+    for params in [
+        FhezParameters::small_test(),
+        FhezParameters::article_line1(),
+    ] {
+        let ctx = GaheContext::new(&params).unwrap();
+        let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+
+        let t = params.t;
+        let message = BigPolynomial {
+            coefficients: (0..ctx.n as u64)
+                .map(|i| BigInt::from((i * 7 + 1) % t))
+                .collect(),
+        };
+        let ciphertext = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &message, &mut ());
+        let decrypted = decrypt_scalar::<BigPolynomial>(&sk, &ctx, &ciphertext, &mut ()).unwrap();
+
+        assert_eq!(
+            decrypted, message,
+            "Decryption failed for n = {} and gamma = {}",
+            ctx.n, ctx.gamma
+        );
+    }
+}
+
+fn random_message(n: usize, t: u64) -> BigPolynomial {
+    let mut rng = rand::thread_rng();
+    BigPolynomial {
+        coefficients: (0..n).map(|_| BigInt::from(rng.gen_range(0..t))).collect(),
+    }
+}
+
+#[test]
+fn test_encrypt_decrypt_vector_roundtrip() {
+    for params in [
+        FhezParameters::small_test(),
+        FhezParameters::article_line1(),
+    ] {
+        let ctx = GaheContext::new(&params).unwrap();
+        let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+        let message = random_message(ctx.n, params.t);
+
+        let ciphertext = encrypt_vector(&sk, &ctx, &params, &message);
+        assert_eq!(ciphertext.components.len(), params.l);
+
+        let decrypted = decrypt_vector(&sk, &ctx, &params, &ciphertext).unwrap();
+        assert_eq!(
+            decrypted, message,
+            "Vector decryption failed for n = {}",
+            ctx.n
+        );
+    }
+}
+
+// each component minus b^i * m must be an encryption of zero with noise below 2^rho
+#[test]
+fn test_vector_ciphertext_internal_structure() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+    let message = random_message(ctx.n, params.t);
+    let ciphertext = encrypt_vector(&sk, &ctx, &params, &message);
+
+    let b_big = BigInt::from(params.b);
+    let noise_bound = BigInt::one() << ctx.rho;
+    for (i, component) in ciphertext.components.iter().enumerate() {
+        let zero = component - &(&b_big.pow(i as u32) * &message);
+        let c_prime = (zero * &sk.k_inv) % &sk.x0;
+        for c in centered_rem_poly(&c_prime, &sk.p).coefficients {
+            assert!(
+                c.abs() <= noise_bound,
+                "Component {i} has noise {c} above 2^rho"
+            );
+        }
+    }
+}
+
+// the DCRT external product of vector(m2) and scalar(m1) decrypts to m1 * m2 mod t
+#[test]
+fn test_external_product_decrypts_product() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+    let dcrt_ctx = DcrtContext::new(&params).unwrap();
+
+    let m1 = random_message(ctx.n, params.t);
+    let m2 = random_message(ctx.n, params.t);
+    let scalar = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m1, &mut ());
+    let vector = encrypt_vector(&sk, &ctx, &params, &m2);
+
+    let vector_dcrt: Vec<Dcrt> = vector
+        .components
+        .iter()
+        .map(|c| to_dcrt(c, &dcrt_ctx, &params))
+        .collect();
+    let mut scalar_dcrt = to_dcrt(&scalar.poly, &dcrt_ctx, &params);
+    let mut product = external_product(
+        &vector_dcrt,
+        &mut scalar_dcrt,
+        &params,
+        &mut DcrtRing {
+            context: &dcrt_ctx,
+            params: &params,
+        },
+    );
+    let product = from_dcrt(&mut product, &dcrt_ctx, &params);
+    let product = ScalarCiphertext::new(product, &sk.x0, scalar.fingerprint);
+
+    let expected = (&m1 * &m2) % &ctx.t;
+    assert_eq!(
+        decrypt_scalar::<BigPolynomial>(&sk, &ctx, &product, &mut ()).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn test_scalar_try_add_decrypts_sum() {
+    let params = FhezParameters::article_line1();
+    let ctx = GaheContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+    let m1 = random_message(ctx.n, params.t);
+    let m2 = random_message(ctx.n, params.t);
+
+    let c1 = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m1, &mut ());
+    let c2 = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m2, &mut ());
+    let sum = c1.try_add(&c2).expect("same key");
+
+    assert_eq!(sum.modulus, sk.x0);
+    assert_eq!(sum.n, ctx.n);
+    assert_eq!(
+        decrypt_scalar::<BigPolynomial>(&sk, &ctx, &sum, &mut ()).unwrap(),
+        (&m1 + &m2) % &ctx.t
+    );
+}
+
+#[test]
+fn test_try_add_rejects_incompatible_ciphertexts() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let mut rng = rand::thread_rng();
+    let sk_1 = GaheSecretKey::generate(&ctx, &mut rng);
+    let sk_2 = GaheSecretKey::generate(&ctx, &mut rng);
+    let message = random_message(ctx.n, params.t);
+
+    let c1 = encrypt_scalar::<BigPolynomial>(&sk_1, &ctx, &message, &mut ());
+    let c2 = encrypt_scalar::<BigPolynomial>(&sk_2, &ctx, &message, &mut ());
+    assert_eq!(c1.try_add(&c2), Err(GaheError::ModulusMismatch));
+
+    let mut same_modulus = c2.clone();
+    same_modulus.modulus = c1.modulus.clone();
+    assert!(matches!(
+        c1.try_add(&same_modulus),
+        Err(GaheError::KeyMismatch { .. })
+    ));
+
+    let (sk_small, ctx_small) = make_test_key(4);
+    let c3 =
+        encrypt_scalar::<BigPolynomial>(&sk_small, &ctx_small, &BigPolynomial::new(4), &mut ());
+    assert_eq!(
+        c1.try_add(&c3),
+        Err(GaheError::DegreeMismatch {
+            left: ctx.n,
+            right: 4
+        })
+    );
+
+    let v1 = encrypt_vector(&sk_1, &ctx, &params, &message);
+    let v2 = encrypt_vector(&sk_2, &ctx, &params, &message);
+    assert!(v1.try_add(&v2).is_err());
+}
+
+#[test]
+fn test_vector_try_add_decrypts_sum() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+    let m1 = random_message(ctx.n, params.t);
+    let m2 = random_message(ctx.n, params.t);
+
+    let v1 = encrypt_vector(&sk, &ctx, &params, &m1);
+    let v2 = encrypt_vector(&sk, &ctx, &params, &m2);
+    let sum = v1.try_add(&v2).expect("same key");
+
+    assert_eq!(
+        decrypt_vector(&sk, &ctx, &params, &sum).unwrap(),
+        (&m1 + &m2) % &ctx.t
+    );
+}
+
+// a key sharing x0 has its own fingerprint, so neither other key can decrypt
+#[test]
+fn test_decrypt_with_another_key_fails() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let mut rng = rand::thread_rng();
+    let sk_1 = GaheSecretKey::generate(&ctx, &mut rng);
+    let sk_2 = GaheSecretKey::generate(&ctx, &mut rng);
+    let sk_3 = sk_1.generate_sharing_modulus(&ctx, &mut rng);
+    assert_ne!(sk_1.fingerprint(&ctx), sk_3.fingerprint(&ctx));
+
+    let ciphertext =
+        encrypt_scalar::<BigPolynomial>(&sk_1, &ctx, &BigPolynomial::new(ctx.n), &mut ());
+    for other in [&sk_2, &sk_3] {
+        assert_eq!(
+            decrypt_scalar::<BigPolynomial>(other, &ctx, &ciphertext, &mut ()),
+            Err(GaheError::KeyMismatch {
+                left: sk_1.fingerprint(&ctx),
+                right: other.fingerprint(&ctx),
+            })
+        );
+    }
+}
+
+// the fingerprint is public, so it must only depend on the key_id and not on k
+#[test]
+fn test_fingerprint_does_not_depend_on_k() {
+    let (sk, ctx) = make_test_key(4);
+    let mut other = GaheSecretKey {
+        k: &BigInt::from(2u32) * &sk.k,
+        ..make_test_key(4).0
+    };
+    assert_eq!(sk.fingerprint(&ctx), other.fingerprint(&ctx));
+
+    other.key_id = 1;
+    assert_ne!(sk.fingerprint(&ctx), other.fingerprint(&ctx));
+}
+
+// checks every homomorphic operation against the plaintext arithmetic mod t
+#[test]
+fn test_homomorphic_operations_match_plaintext() {
+    let params = FhezParameters::article_line1();
+    let ctx = GaheContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+    let m1 = random_message(ctx.n, params.t);
+    let m2 = random_message(ctx.n, params.t);
+    let c1 = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m1, &mut ());
+    let c2 = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m2, &mut ());
+
+    let sum = c1.try_add(&c2).unwrap();
+    assert_eq!(
+        decrypt_scalar::<BigPolynomial>(&sk, &ctx, &sum, &mut ()).unwrap(),
+        (&m1 + &m2) % &ctx.t
+    );
+
+    let difference = c1.try_sub(&c2).unwrap();
+    assert_eq!(
+        decrypt_scalar::<BigPolynomial>(&sk, &ctx, &difference, &mut ()).unwrap(),
+        (&m1 - &m2) % &ctx.t
+    );
+
+    let negated = -&c1;
+    assert_eq!(
+        decrypt_scalar::<BigPolynomial>(&sk, &ctx, &negated, &mut ()).unwrap(),
+        (&BigPolynomial::new(ctx.n) - &m1) % &ctx.t
+    );
+
+    for a in [0i64, 1, 3, -5, 1000] {
+        let a = BigInt::from(a);
+        let scaled = c1.mul_scalar(&a);
+        assert_eq!(
+            decrypt_scalar::<BigPolynomial>(&sk, &ctx, &scaled, &mut ()).unwrap(),
+            (&a * &m1) % &ctx.t,
+            "Multiplication by {a} failed"
+        );
+    }
+
+    let plain = random_message(ctx.n, params.t);
+    let product = c1.mul_plain(&plain).unwrap();
+    assert_eq!(
+        decrypt_scalar::<BigPolynomial>(&sk, &ctx, &product, &mut ()).unwrap(),
+        (&plain * &m1) % &ctx.t
+    );
+
+    // c1 - c2 + c2 * 2 = m1 + m2
+    let combined = difference
+        .try_add(&c2.mul_scalar(&BigInt::from(2)))
+        .unwrap();
+    assert_eq!(
+        decrypt_scalar::<BigPolynomial>(&sk, &ctx, &combined, &mut ()).unwrap(),
+        (&m1 + &m2) % &ctx.t
+    );
+}
+
+#[test]
+fn test_homomorphic_operations_stay_reduced() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+    let c = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &random_message(ctx.n, params.t), &mut ());
+
+    let results = [
+        c.try_sub(&c).unwrap(),
+        -&c,
+        c.mul_scalar(&BigInt::from(-7)),
+        c.mul_plain(&random_message(ctx.n, 100)).unwrap(),
+    ];
+    for result in results {
+        assert!(result
+            .poly
+            .coefficients
+            .iter()
+            .all(|coef| !coef.is_negative() && coef < &sk.x0));
+    }
+
+    assert_eq!(
+        c.mul_plain(&BigPolynomial::new(4)),
+        Err(GaheError::DegreeMismatch {
+            left: ctx.n,
+            right: 4
+        })
+    );
+}
+
+#[test]
+fn test_external_product_poly_decrypts_product() {
+    let params = FhezParameters::article_line1();
+    let ctx = GaheContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+    let m1 = random_message(ctx.n, params.t);
+    let m2 = random_message(ctx.n, params.t);
+
+    let scalar = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m1, &mut ());
+    let vector = encrypt_vector(&sk, &ctx, &params, &m2);
+    let product = external_product_poly(&vector, &scalar, &params).unwrap();
+
+    assert_eq!(
+        decrypt_scalar::<BigPolynomial>(&sk, &ctx, &product, &mut ()).unwrap(),
+        (&m1 * &m2) % &ctx.t
+    );
+
+    let other_sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+    let other_vector = encrypt_vector(&other_sk, &ctx, &params, &m2);
+    assert!(external_product_poly(&other_vector, &scalar, &params).is_err());
+}
+
+// the DCRT external product must give back exactly the BigPolynomial reference
+#[test]
+fn test_external_product_dcrt_matches_poly() {
+    for (params, backend) in [
+        (FhezParameters::small_test(), DcrtBackend::Fft),
+        (FhezParameters::article_line1(), DcrtBackend::Fft),
+        (FhezParameters::small_test(), DcrtBackend::Ntt),
+        (FhezParameters::article_line1(), DcrtBackend::Ntt),
+    ] {
+        let ctx = GaheContext::new(&params).unwrap();
+        let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+        let dcrt_ctx = DcrtContext::with_backend(&params, backend).unwrap();
+
+        let scalar =
+            encrypt_scalar::<BigPolynomial>(&sk, &ctx, &random_message(ctx.n, params.t), &mut ());
+        let vector = encrypt_vector(&sk, &ctx, &params, &random_message(ctx.n, params.t));
+        let expected = external_product_poly(&vector, &scalar, &params).unwrap();
+
+        let vector_dcrt: Vec<Dcrt> = vector
+            .components
+            .iter()
+            .map(|c| to_dcrt(c, &dcrt_ctx, &params))
+            .collect();
+        let mut scalar_dcrt = to_dcrt(&scalar.poly, &dcrt_ctx, &params);
+        let mut product = external_product(
+            &vector_dcrt,
+            &mut scalar_dcrt,
+            &params,
+            &mut DcrtRing {
+                context: &dcrt_ctx,
+                params: &params,
+            },
+        );
+        let product = from_dcrt(&mut product, &dcrt_ctx, &params);
+
+        assert_eq!(
+            ScalarCiphertext::new(product, &sk.x0, scalar.fingerprint),
+            expected,
+            "{backend:?} and BigPolynomial external products differ for n = {}",
+            params.n
+        );
+    }
+}
+
+// with the deterministic encryption the measured noise is exactly r
+#[test]
+fn test_noise_of_matches_encryption_noise() {
+    let n = 4;
+    let (sk, ctx) = make_test_key(n);
+    let message = BigPolynomial {
+        coefficients: vec![
+            BigInt::from(2u32),
+            BigInt::from(0u32),
+            BigInt::from(1u32),
+            BigInt::from(1u32),
+        ],
+    };
+    let q = BigPolynomial {
+        coefficients: vec![
+            BigInt::from(1u32),
+            BigInt::from(0u32),
+            BigInt::from(2u32),
+            BigInt::from(0u32),
+        ],
+    };
+    let r = BigPolynomial {
+        coefficients: vec![
+            BigInt::from(0i32),
+            BigInt::from(1i32),
+            BigInt::from(-1i32),
+            BigInt::from(-2i32),
+        ],
+    };
+
+    let ciphertext = encrypt_deterministic(&sk, &ctx, &message, &q, &r);
+    let (decrypted, measurement) = decrypt_with_noise(&sk, &ctx, &ciphertext).unwrap();
+
+    assert_eq!(decrypted, message);
+    assert_eq!(measurement.noise, r);
+    assert_eq!(measurement.bits, 2);
+    assert_eq!(measurement, noise_of(&sk, &ctx, &ciphertext).unwrap());
+}
+
+fn infinity_norm(poly: &BigPolynomial) -> BigInt {
+    poly.coefficients
+        .iter()
+        .map(|c| c.abs())
+        .max()
+        .unwrap_or_default()
+}
+
+// the noise after each homomorphic operation stays below its worst case bound
+#[test]
+fn test_noise_growth_after_operations() {
+    let params = FhezParameters::article_line1();
+    let ctx = GaheContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+    let n = BigInt::from(ctx.n);
+    let t = &ctx.t;
+    let fresh_bound = BigInt::one() << ctx.rho;
+
+    let m1 = random_message(ctx.n, params.t);
+    let m2 = random_message(ctx.n, params.t);
+    let c1 = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m1, &mut ());
+    let c2 = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m2, &mut ());
+
+    let fresh = noise_of(&sk, &ctx, &c1).unwrap();
+    assert!(infinity_norm(&fresh.noise) <= fresh_bound);
+    assert!(fresh.bits <= ctx.rho + 1);
+    assert!(fresh.budget > 0);
+
+    // r1 + r2 plus the rounding of round(p / t) * t against p
+    let sum = noise_of(&sk, &ctx, &c1.try_add(&c2).unwrap()).unwrap();
+    assert!(infinity_norm(&sum.noise) <= &fresh_bound * 2 + t);
+
+    let a = BigInt::from(1000);
+    let scaled = noise_of(&sk, &ctx, &c1.mul_scalar(&a)).unwrap();
+    assert!(infinity_norm(&scaled.noise) <= &a * (&fresh_bound + t));
+    assert!(scaled.bits > fresh.bits);
+
+    // l * n * b / 2 * 2^rho from the decomposition plus ||m2||_1 * (2^rho + t)
+    let vector = encrypt_vector(&sk, &ctx, &params, &m2);
+    let product = external_product_poly(&vector, &c1, &params).unwrap();
+    let product = noise_of(&sk, &ctx, &product).unwrap();
+    let bound = BigInt::from(params.l) * &n * BigInt::from(params.b / 2) * &fresh_bound
+        + &n * t * (&fresh_bound + t);
+    assert!(infinity_norm(&product.noise) <= bound);
+    assert!(product.budget > 0 && product.budget < fresh.budget);
+}
+
+// a ciphertext moved to a key sharing x0 decrypts there with the gadget noise on top
+#[test]
+fn test_key_switch_decrypts_under_new_key() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let mut rng = rand::thread_rng();
+    let sk = GaheSecretKey::generate(&ctx, &mut rng);
+    let other = sk.generate_sharing_modulus(&ctx, &mut rng);
+    assert_eq!(other.p, sk.p);
+    assert_ne!(other.k, sk.k);
+
+    let forward = KeySwitchingKey::generate(&sk, &other, &ctx, &params, &mut rng).unwrap();
+    let back = KeySwitchingKey::generate(&other, &sk, &ctx, &params, &mut rng).unwrap();
+    let model = NoiseModel::new(&params);
+
+    for _ in 0..3 {
+        let message = random_message(ctx.n, params.t);
+        let ciphertext = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &message, &mut ());
+        let fresh = infinity_norm(&noise_of(&sk, &ctx, &ciphertext).unwrap().noise);
+
+        let switched = key_switch(&ciphertext, &forward, &params).unwrap();
+        assert_eq!(switched.fingerprint, other.fingerprint(&ctx));
+        let (decrypted, measurement) = decrypt_with_noise(&other, &ctx, &switched).unwrap();
+        assert_eq!(decrypted, message);
+        let bound = BigInt::from_f64(model.key_switch(fresh.to_f64().unwrap())).unwrap();
+        assert!(
+            infinity_norm(&measurement.noise) <= bound,
+            "Switched noise above the bound {bound}"
+        );
+
+        // Switching back adds the gadget noise once more
+        let restored = key_switch(&switched, &back, &params).unwrap();
+        let (decrypted, measurement) = decrypt_with_noise(&sk, &ctx, &restored).unwrap();
+        assert_eq!(decrypted, message);
+        let bound = BigInt::from_f64(model.key_switch(bound.to_f64().unwrap())).unwrap();
+        assert!(infinity_norm(&measurement.noise) <= bound);
+    }
+}
+
+// the switched ciphertext belongs to the new key only, and the key only takes its source
+#[test]
+fn test_key_switch_rejects_the_other_key() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let mut rng = rand::thread_rng();
+    let sk = GaheSecretKey::generate(&ctx, &mut rng);
+    let other = sk.generate_sharing_modulus(&ctx, &mut rng);
+    let forward = KeySwitchingKey::generate(&sk, &other, &ctx, &params, &mut rng).unwrap();
+
+    let ciphertext =
+        encrypt_scalar::<BigPolynomial>(&sk, &ctx, &random_message(ctx.n, params.t), &mut ());
+    let switched = key_switch(&ciphertext, &forward, &params).unwrap();
+    assert_eq!(
+        decrypt_scalar::<BigPolynomial>(&sk, &ctx, &switched, &mut ()),
+        Err(GaheError::KeyMismatch {
+            left: other.fingerprint(&ctx),
+            right: sk.fingerprint(&ctx),
+        })
+    );
+
+    // Switching twice with the same key is rejected too
+    assert_eq!(
+        key_switch(&switched, &forward, &params),
+        Err(GaheError::KeyMismatch {
+            left: other.fingerprint(&ctx),
+            right: sk.fingerprint(&ctx),
+        })
+    );
+}
+
+#[test]
+fn test_key_switching_key_needs_the_same_modulus() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let mut rng = rand::thread_rng();
+    let sk = GaheSecretKey::generate(&ctx, &mut rng);
+    let unrelated = GaheSecretKey::generate(&ctx, &mut rng);
+    assert_eq!(
+        KeySwitchingKey::generate(&sk, &unrelated, &ctx, &params, &mut rng),
+        Err(GaheError::ModulusMismatch)
+    );
+}
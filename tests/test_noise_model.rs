@@ -0,0 +1,96 @@
+use implementing_fhez::*;
+
+fn random_message(n: usize, t: u64) -> BigPolynomial {
+    let mut rng = rand::thread_rng();
+    BigPolynomial {
+        coefficients: (0..n).map(|_| BigInt::from(rng.gen_range(0..t))).collect(),
+    }
+}
+
+fn infinity_norm(poly: &BigPolynomial) -> f64 {
+    poly.coefficients
+        .iter()
+        .map(|c| c.abs().to_f64().unwrap())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn test_estimate_article_line1() {
+    let params = FhezParameters::article_line1();
+    let model = NoiseModel::new(&params);
+    let estimate = model.estimate();
+
+    assert_eq!(estimate.fresh, 65536.0);
+    assert_eq!(estimate.addition, 2.0 * 65536.0 + 2.0);
+    // 2^95 / 8 against 2^16
+    assert_eq!(estimate.decryption_bound, 2f64.powi(92));
+    assert_eq!(estimate.margin, 76.0);
+    assert!(estimate.external_product > model.gadget_noise());
+    assert_eq!(estimate.max_depth, 5);
+}
+
+#[test]
+fn test_max_depth_is_the_last_level_that_decrypts() {
+    for params in [
+        FhezParameters::small_test(),
+        FhezParameters::article_line1(),
+    ] {
+        let model = NoiseModel::new(&params);
+        let depth = model.max_depth();
+
+        let mut noise = model.fresh();
+        for _ in 0..depth {
+            noise = model.external_product(noise);
+            assert!(model.margin(noise) > 0.0);
+        }
+        noise = model.external_product(noise);
+        assert!(model.margin(noise) <= 0.0);
+    }
+}
+
+// bootstrapping is only useful if its output can still be decrypted
+#[test]
+fn test_bootstrap_fits_in_the_decryption_bound() {
+    let model = NoiseModel::new(&FhezParameters::small_test());
+    assert!(model.bootstrap() > model.gadget_noise());
+    assert!(model.margin(model.bootstrap()) > 0.0);
+}
+
+// the measured noise of real ciphertexts stays below the model at every level
+#[test]
+fn test_model_bounds_measured_noise() {
+    let params = FhezParameters::article_line1();
+    let model = NoiseModel::new(&params);
+    let ctx = GaheContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+
+    let m1 = random_message(ctx.n, params.t);
+    let m2 = random_message(ctx.n, params.t);
+    let c1 = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m1, &mut ());
+    let c2 = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m2, &mut ());
+
+    let fresh = infinity_norm(&noise_of(&sk, &ctx, &c1).unwrap().noise);
+    assert!(fresh <= model.fresh());
+    let sum = infinity_norm(
+        &noise_of(&sk, &ctx, &c1.try_add(&c2).unwrap())
+            .unwrap()
+            .noise,
+    );
+    assert!(sum <= model.add(model.fresh(), model.fresh()));
+
+    // Chains max_depth external products and checks each level against the model
+    let mut message = m1;
+    let mut ciphertext = c1;
+    let mut bound = model.fresh();
+    for _ in 0..model.max_depth() {
+        let factor = random_message(ctx.n, params.t);
+        let vector = encrypt_vector(&sk, &ctx, &params, &factor);
+        ciphertext = external_product_poly(&vector, &ciphertext, &params).unwrap();
+        message = (&message * &factor) % &ctx.t;
+        bound = model.external_product(bound);
+
+        let (decrypted, measurement) = decrypt_with_noise(&sk, &ctx, &ciphertext).unwrap();
+        assert_eq!(decrypted, message);
+        assert!(infinity_norm(&measurement.noise) <= bound);
+    }
+}
@@ -0,0 +1,136 @@
+use implementing_fhez::*;
+
+#[test]
+fn test_presets_are_valid() {
+    assert_eq!(FhezParameters::article_line1().validate(), Ok(()));
+    assert_eq!(FhezParameters::small_test().validate(), Ok(()));
+}
+
+#[test]
+fn test_validate_rejects_each_condition() {
+    let valid = FhezParameters::small_test();
+
+    let params = FhezParameters {
+        n: 96,
+        ..valid.clone()
+    };
+    assert_eq!(params.validate(), Err(ParamError::InvalidDegree { n: 96 }));
+
+    let params = FhezParameters {
+        t: 1,
+        ..valid.clone()
+    };
+    assert_eq!(
+        params.validate(),
+        Err(ParamError::InvalidMessageModulus { t: 1 })
+    );
+
+    // 7 * 20 = 140 bits do not cover gamma = 150
+    let params = FhezParameters {
+        l: 7,
+        ..valid.clone()
+    };
+    assert_eq!(
+        params.validate(),
+        Err(ParamError::GadgetTooShort {
+            coverage_bits: 140.0,
+            gamma: 150.0
+        })
+    );
+
+    // b^l = 2^150 = 2^gamma has no room for the carry of the centered digits
+    let params = FhezParameters {
+        l: 15,
+        b: 1 << 10,
+        ..valid.clone()
+    };
+    assert_eq!(
+        params.validate(),
+        Err(ParamError::GadgetTooShort {
+            coverage_bits: 150.0,
+            gamma: 150.0
+        })
+    );
+    let params = FhezParameters {
+        gamma: 149.0,
+        ..params
+    };
+    assert!(params.validate_scheme().is_ok());
+
+    let params = FhezParameters {
+        eta: 150,
+        ..valid.clone()
+    };
+    assert!(matches!(
+        params.validate(),
+        Err(ParamError::PrimeTooLarge { eta: 150, .. })
+    ));
+
+    // p / 2t is at least 2^61 for eta = 64 and t = 2
+    let params = FhezParameters {
+        rho: 60,
+        ..valid.clone()
+    };
+    assert_eq!(params.validate(), Ok(()));
+    let params = FhezParameters {
+        rho: 61,
+        ..valid.clone()
+    };
+    assert_eq!(
+        params.validate(),
+        Err(ParamError::NoiseTooLarge {
+            rho: 61,
+            eta: 64,
+            t: 2
+        })
+    );
+
+    let params = FhezParameters {
+        primes: &PRIMES_20[..5],
+        ..valid.clone()
+    };
+    assert!(matches!(
+        params.validate(),
+        Err(ParamError::InsufficientPrimes {
+            available_bits: 101,
+            ..
+        })
+    ));
+
+    // 2 * 20 + 13 + 3 bits exceed the 53 bits of the f64 mantissa
+    let params = FhezParameters {
+        n: 1 << 13,
+        ..valid
+    };
+    assert!(matches!(
+        params.validate(),
+        Err(ParamError::FftPrecision {
+            mantissa_bits: 53,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_contexts_reject_invalid_parameters() {
+    let params = FhezParameters {
+        primes: &PRIMES_15[..2],
+        ..FhezParameters::small_test()
+    };
+    assert!(matches!(
+        DcrtContext::new(&params),
+        Err(ParamError::InsufficientPrimes { .. })
+    ));
+    // The primes only matter to the DCRT
+    assert!(GaheContext::new(&params).is_ok());
+
+    let params = FhezParameters {
+        rho: 80,
+        ..FhezParameters::small_test()
+    };
+    assert!(GaheContext::new(&params).is_err());
+    assert_eq!(
+        GaheContext::new(&params).unwrap_err().to_string(),
+        "noise of 80 bits does not fit in p / 2t for eta = 64 and t = 2"
+    );
+}
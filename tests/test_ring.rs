@@ -0,0 +1,160 @@
+use implementing_fhez::*;
+
+struct Backend {
+    params: FhezParameters,
+    context: DcrtContext,
+}
+
+fn backends(params: FhezParameters) -> Vec<Backend> {
+    [DcrtBackend::Fft, DcrtBackend::Ntt]
+        .into_iter()
+        .map(|backend| Backend {
+            context: DcrtContext::with_backend(&params, backend).unwrap(),
+            params: params.clone(),
+        })
+        .collect()
+}
+
+fn random_message(n: usize, t: u64) -> BigPolynomial {
+    let mut rng = rand::thread_rng();
+    BigPolynomial {
+        coefficients: (0..n).map(|_| BigInt::from(rng.gen_range(0..t))).collect(),
+    }
+}
+
+// the same operations through the trait must agree on both representations
+#[test]
+fn test_ring_operations_agree() {
+    for backend in backends(FhezParameters::small_test()) {
+        let n = backend.params.n;
+        let a = BigPolynomial::rand(n, 40, n as u32);
+        let b = BigPolynomial::rand(n, 40, n as u32);
+        let scalar = BigInt::from(-12345);
+        let mut ring = DcrtRing {
+            context: &backend.context,
+            params: &backend.params,
+        };
+
+        let a_dcrt = Dcrt::from_coefficients(&a, &mut ring);
+        let b_dcrt = Dcrt::from_coefficients(&b, &mut ring);
+        assert_eq!(a_dcrt.to_coefficients(&mut ring), a);
+        assert_eq!(
+            PolyRing::add(&a_dcrt, &b_dcrt).to_coefficients(&mut ring),
+            PolyRing::add(&a, &b)
+        );
+        assert_eq!(
+            PolyRing::sub(&a_dcrt, &b_dcrt).to_coefficients(&mut ring),
+            PolyRing::sub(&a, &b)
+        );
+        assert_eq!(
+            PolyRing::mul(&a_dcrt, &b_dcrt).to_coefficients(&mut ring),
+            PolyRing::mul(&a, &b)
+        );
+        assert_eq!(
+            PolyRing::neg(&a_dcrt).to_coefficients(&mut ring),
+            PolyRing::neg(&a)
+        );
+        assert_eq!(
+            a_dcrt
+                .mul_scalar(&scalar, &mut ring)
+                .to_coefficients(&mut ring),
+            a.mul_scalar(&scalar, &mut ())
+        );
+    }
+}
+
+// a scalar as large as the modulus allows scales a product exactly on both backends
+#[test]
+fn test_mul_scalar_large_scalar_is_exact() {
+    for backend in backends(FhezParameters::small_test()) {
+        let n = backend.params.n;
+        let a = BigPolynomial::rand(n, 40, n as u32);
+        let b = BigPolynomial::rand(n, 40, n as u32);
+        let mut ring = DcrtRing {
+            context: &backend.context,
+            params: &backend.params,
+        };
+
+        let product = PolyRing::mul(&a, &b);
+        let product_bits = product.coefficients.iter().map(|c| c.bits()).max().unwrap();
+        let scalar = -(BigInt::one() << (backend.context.m.bits() - product_bits - 2)) + 1;
+
+        let product_dcrt = PolyRing::mul(
+            &Dcrt::from_coefficients(&a, &mut ring),
+            &Dcrt::from_coefficients(&b, &mut ring),
+        );
+        assert_eq!(
+            product_dcrt
+                .mul_scalar(&scalar, &mut ring)
+                .to_coefficients(&mut ring),
+            product.mul_scalar(&scalar, &mut ())
+        );
+    }
+}
+
+// products of gamma bit polynomials do not fit in the DCRT modulus without the gadget
+#[test]
+fn test_mul_coefficients_reduces_large_operands() {
+    for backend in backends(FhezParameters::article_line1()) {
+        let n = backend.params.n;
+        let x0 = sample_prime(206, &mut rand::thread_rng());
+        let a = BigPolynomial::rand(n, 206, n as u32) % &x0;
+        let b = BigPolynomial::rand(n, 206, n as u32) % &x0;
+        let mut ring = DcrtRing {
+            context: &backend.context,
+            params: &backend.params,
+        };
+
+        assert_eq!(
+            Dcrt::mul_coefficients(&a, &b, &x0, &mut ring),
+            BigPolynomial::mul_coefficients(&a, &b, &x0, &mut ())
+        );
+    }
+}
+
+// the scheme gives the same results whichever representation computes the products
+#[test]
+fn test_gahe_runs_on_every_backend() {
+    let params = FhezParameters::small_test();
+    let ctx = GaheContext::new(&params).unwrap();
+    let sk = GaheSecretKey::generate(&ctx, &mut rand::thread_rng());
+
+    for backend in backends(params.clone()) {
+        let mut ring = DcrtRing {
+            context: &backend.context,
+            params: &backend.params,
+        };
+        let m1 = random_message(ctx.n, params.t);
+        let m2 = random_message(ctx.n, params.t);
+
+        let c1 = encrypt_scalar::<Dcrt>(&sk, &ctx, &m1, &mut ring);
+        assert_eq!(
+            decrypt_scalar::<BigPolynomial>(&sk, &ctx, &c1, &mut ()).unwrap(),
+            m1
+        );
+        let c2 = encrypt_scalar::<BigPolynomial>(&sk, &ctx, &m2, &mut ());
+        assert_eq!(
+            decrypt_scalar::<Dcrt>(&sk, &ctx, &c2, &mut ring).unwrap(),
+            m2
+        );
+
+        let vector = encrypt_vector(&sk, &ctx, &params, &m2);
+        let components: Vec<Dcrt> = vector
+            .components
+            .iter()
+            .map(|c| Dcrt::from_coefficients(c, &mut ring))
+            .collect();
+        let mut scalar = Dcrt::from_coefficients(&c1.poly, &mut ring);
+        let product = external_product(&components, &mut scalar, &params, &mut ring);
+        let product =
+            ScalarCiphertext::new(product.to_coefficients(&mut ring), &sk.x0, c1.fingerprint);
+        assert_eq!(
+            product,
+            external_product_poly(&vector, &c1, &params).unwrap()
+        );
+        assert_eq!(
+            decrypt_scalar::<Dcrt>(&sk, &ctx, &product, &mut ring).unwrap(),
+            (&m1 * &m2) % &ctx.t
+        );
+    }
+}
@@ -135,6 +135,7 @@ mod tests {
             l: 3,
             gamma: 20.0,
             rho: 4,
+            eta: 8,
             t: 17,
             primes: &[],
         };
@@ -188,6 +189,7 @@ mod tests {
             l: 3,
             gamma: 20.0,
             rho: 4,
+            eta: 8,
             t: 17,
             primes: &[],
         };
@@ -263,7 +265,7 @@ mod tests {
             ],
         };
         let result = centered_rem_poly(&poly, &modulus);
-        let expected = vec![0i64, 1, 5, -4, -1, 5, 4, 0];
+        let expected = [0i64, 1, 5, -4, -1, 5, 4, 0];
         for (i, (got, exp)) in result.coefficients.iter().zip(expected.iter()).enumerate() {
             assert_eq!(
                 got,
@@ -297,6 +299,28 @@ mod tests {
         }
     }
 
+    // =========================================================================
+    // mod_switch
+    // =========================================================================
+    #[test]
+    fn test_mod_switch() {
+        let cases = [
+            (7i64, 100i64, 10i64, 1i64), // 0.7 -> 1
+            (45, 100, 10, 5),            // 4.5 -> 5 half up
+            (-3, 100, 10, 0),            // 97 -> 9.7 -> 10 = 0 mod 10
+            (250, 100, 16, 8),           // 50 -> 8
+            (0, 100, 16, 0),
+        ];
+        for (a, from, to, expected) in cases {
+            let result = mod_switch(&BigInt::from(a), &BigInt::from(from), &BigInt::from(to));
+            assert_eq!(
+                result,
+                BigInt::from(expected),
+                "switch({a} mod {from} to {to}): got {result}, expected {expected}"
+            );
+        }
+    }
+
     // =========================================================================
     // sample_d
     // =========================================================================
@@ -352,4 +376,35 @@ mod tests {
             );
         }
     }
+
+    // =========================================================================
+    // is_probable_prime / sample_prime
+    // =========================================================================
+    #[test]
+    fn test_is_probable_prime() {
+        let mut rng = rand::thread_rng();
+        for p in [2i64, 3, 5, 19, 41, 1048583, 2147483647] {
+            assert!(
+                is_probable_prime(&BigInt::from(p), 20, &mut rng),
+                "{p} is prime"
+            );
+        }
+        // 561 and 41041 are Carmichael numbers
+        for c in [-7i64, 0, 1, 4, 561, 41041, 1048583 * 1048589] {
+            assert!(
+                !is_probable_prime(&BigInt::from(c), 20, &mut rng),
+                "{c} is not prime"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_prime_bit_length() {
+        let mut rng = rand::thread_rng();
+        for bits in [2u64, 5, 20, 64] {
+            let p = sample_prime(bits, &mut rng);
+            assert_eq!(p.bits(), bits, "prime {p} must have {bits} bits");
+            assert!(is_probable_prime(&p, 20, &mut rng));
+        }
+    }
 }